@@ -0,0 +1,62 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+
+use tesuji::parse_sgf;
+use tesuji::sgf::{BitBoard, Board, Cell, GameTree, NodeId, count_liberties, find_group};
+
+/// A 60-move game record with a few captures, shared with the unit tests.
+const GAME: &str = include_str!("game.sgf");
+
+fn game() -> (GameTree, NodeId) {
+    let tree = parse_sgf(GAME).unwrap();
+    let leaf = tree.iter_leaves(tree.roots[0]).next().unwrap().0;
+    (tree, leaf)
+}
+
+fn bench_replay(c: &mut Criterion) {
+    let (tree, leaf) = game();
+    let mut group = c.benchmark_group("replay_60_moves");
+    group.bench_function("board", |b| {
+        b.iter(|| Board::from_tree(black_box(&tree), black_box(leaf)))
+    });
+    group.bench_function("bitboard", |b| {
+        b.iter(|| BitBoard::from_tree(black_box(&tree), black_box(leaf)))
+    });
+    group.finish();
+}
+
+fn bench_groups(c: &mut Criterion) {
+    let (tree, leaf) = game();
+    let board = Board::from_tree(&tree, leaf);
+    let bits = BitBoard::from(&board);
+    let stones: Vec<(usize, usize)> = (0..19)
+        .flat_map(|row| (0..19).map(move |col| (row, col)))
+        .filter(|&(row, col)| board.cells[row][col] != Cell::Empty)
+        .collect();
+
+    let mut group = c.benchmark_group("all_groups_and_liberties");
+    group.bench_function("board", |b| {
+        b.iter(|| {
+            stones
+                .iter()
+                .map(|&(row, col)| {
+                    let g = find_group(&board.cells, row, col, 19);
+                    count_liberties(&board.cells, &g, 19)
+                })
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("bitboard", |b| {
+        b.iter(|| {
+            stones
+                .iter()
+                .map(|&(row, col)| bits.count_liberties(&bits.find_group(row, col)))
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_replay, bench_groups);
+criterion_main!(benches);
@@ -16,7 +16,10 @@ use iced::{
 };
 
 use crate::gui::{Message, assets::BoardAssets, theme};
-use tesuji::sgf::{Board, Cell};
+use tesuji::{
+    render::{col_label, star_points},
+    sgf::{Board, Cell},
+};
 
 /// Stone radius as a fraction of cell size.
 pub const STONE_RADIUS_RATIO: f32 = 0.48;
@@ -653,17 +656,6 @@ impl BoardMetrics {
     }
 }
 
-/// Go column letter for a given column index (0-based). Skips 'I'.
-fn col_label(col: usize) -> char {
-    let c = b'A' + col as u8;
-    // Skip 'I' to avoid confusion with the digit '1'
-    if c >= b'I' {
-        (c + 1) as char
-    } else {
-        c as char
-    }
-}
-
 /// Generate coordinate label primitives for all 4 sides of the board.
 fn build_labels(metrics: &BoardMetrics) -> Vec<DrawPrimitive> {
     let size = metrics.board_size;
@@ -736,25 +728,6 @@ fn build_labels(metrics: &BoardMetrics) -> Vec<DrawPrimitive> {
     out
 }
 
-fn star_points(board_size: usize) -> &'static [(usize, usize)] {
-    match board_size {
-        19 => &[
-            (3, 3),
-            (3, 9),
-            (3, 15),
-            (9, 3),
-            (9, 9),
-            (9, 15),
-            (15, 3),
-            (15, 9),
-            (15, 15),
-        ],
-        13 => &[(3, 3), (3, 9), (6, 6), (9, 3), (9, 9)],
-        9 => &[(2, 2), (2, 6), (4, 4), (6, 2), (6, 6)],
-        _ => &[],
-    }
-}
-
 impl<'a> advanced::Widget<Message, iced::Theme, iced::Renderer> for BoardWidget<'a> {
     fn size(&self) -> Size<Length> {
         Size::new(Length::Fill, Length::Fill)
@@ -2,6 +2,7 @@ pub mod assets;
 pub mod board;
 pub mod hotkeys;
 pub mod io;
+pub mod raster;
 pub mod theme;
 pub mod tree_panel;
 
@@ -13,9 +14,7 @@ use iced::{
 };
 
 use tesuji::sgf::node::GoCoord;
-use tesuji::sgf::{
-    Board, Cell, GameTree, NodeId, SGFProperty, count_liberties, find_group, orthogonal_neighbors,
-};
+use tesuji::sgf::{Board, BoardCursor, Cell, GameSetup, GameTree, NodeId, SGFProperty};
 use tesuji::{EditCommand, Editor, parse_sgf, write_sgf};
 
 use crate::gui::{
@@ -27,11 +26,9 @@ use crate::gui::{
 pub struct GuiApp {
     pub editor: Editor,
     pub file_path: Option<PathBuf>,
-    pub cached_board: Board,
-    /// Board history for incremental back-navigation.
-    /// Each entry is the board state *before* applying the node at the
-    /// corresponding depth along the root→cursor path.
-    pub board_history: Vec<Board>,
+    /// Board position kept in step with the editor cursor; moving along the
+    /// tree only applies or unapplies the nodes in between.
+    pub board_cursor: BoardCursor,
     pub active_game_index: usize,
     pub status_message: Option<StatusMessage>,
     pub hover_coord: Option<(usize, usize)>,
@@ -126,13 +123,12 @@ impl GuiApp {
     pub fn new() -> (Self, Task<Message>) {
         let tree = new_game_tree();
         let editor = Editor::new(tree);
-        let cached_board = Board::from_tree(&editor.tree, editor.cursor);
+        let board_cursor = BoardCursor::new(&editor.tree, editor.cursor);
         (
             Self {
                 editor,
                 file_path: None,
-                cached_board,
-                board_history: Vec::new(),
+                board_cursor,
                 active_game_index: 0,
                 status_message: None,
                 hover_coord: None,
@@ -144,11 +140,17 @@ impl GuiApp {
         )
     }
 
-    /// Recompute the board from scratch and rebuild the history stack.
+    /// Recompute the board from scratch, e.g. after the tree was edited.
     fn recompute_board(&mut self) {
-        let (board, history) = board_with_history(&self.editor.tree, self.editor.cursor);
-        self.cached_board = board;
-        self.board_history = history;
+        self.board_cursor
+            .reset(&self.editor.tree, self.editor.cursor);
+    }
+
+    /// Bring the board to the editor cursor after navigation or adding a
+    /// move, applying only the nodes in between.
+    fn sync_board(&mut self) {
+        self.board_cursor
+            .seek(&self.editor.tree, self.editor.cursor);
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
@@ -231,18 +233,13 @@ impl GuiApp {
                             })
                             .copied();
                         if let Some(child_id) = existing {
-                            // Navigate to existing variation: push board, apply child node
-                            self.board_history.push(self.cached_board.clone());
+                            // Navigate to existing variation
                             self.editor.cursor = child_id;
-                            self.cached_board
-                                .apply_node(self.editor.tree.node(child_id));
                         } else {
-                            // New move: push board, apply command (which advances cursor)
-                            self.board_history.push(self.cached_board.clone());
+                            // New move: the command advances the cursor
                             self.editor.apply(EditCommand::AddMove(prop));
-                            self.cached_board
-                                .apply_node(self.editor.tree.node(self.editor.cursor));
                         }
+                        self.sync_board();
                     }
                     Err(msg) => {
                         self.status_message = Some(StatusMessage::error(msg));
@@ -256,16 +253,14 @@ impl GuiApp {
                 // Reserved for future use (e.g. resolution-dependent rendering).
             }
             Message::PassRequested => {
-                let color = current_player(&self.cached_board);
+                let color = current_player(self.board_cursor.board());
                 let prop = match color {
                     Cell::Black => SGFProperty::B(GoCoord::pass()),
                     Cell::White => SGFProperty::W(GoCoord::pass()),
                     Cell::Empty => unreachable!(),
                 };
-                self.board_history.push(self.cached_board.clone());
                 self.editor.apply(EditCommand::AddMove(prop));
-                self.cached_board
-                    .apply_node(self.editor.tree.node(self.editor.cursor));
+                self.sync_board();
             }
             Message::DeleteNodeConfirmed => {
                 self.confirm_delete = false;
@@ -279,60 +274,50 @@ impl GuiApp {
                 let old_cursor = self.editor.cursor;
                 self.editor.apply(EditCommand::NavigateNext);
                 if self.editor.cursor != old_cursor {
-                    // Incremental forward: push current board, apply next node
-                    self.board_history.push(self.cached_board.clone());
-                    self.cached_board
-                        .apply_node(self.editor.tree.node(self.editor.cursor));
+                    self.sync_board();
                 }
             }
             Message::NavigatePrev => {
                 let old_cursor = self.editor.cursor;
                 self.editor.apply(EditCommand::NavigatePrev);
                 if self.editor.cursor != old_cursor {
-                    // Incremental backward: pop from history
-                    if let Some(prev_board) = self.board_history.pop() {
-                        self.cached_board = prev_board;
-                    } else {
-                        self.recompute_board();
-                    }
+                    self.sync_board();
                 }
             }
             Message::NavigateNextVariation => {
                 self.editor.apply(EditCommand::NavigateNextVariation);
-                self.recompute_board();
+                self.sync_board();
             }
             Message::NavigatePrevVariation => {
                 self.editor.apply(EditCommand::NavigatePrevVariation);
-                self.recompute_board();
+                self.sync_board();
             }
             Message::NavigateFirstVariation => {
                 self.editor.apply(EditCommand::NavigateFirstVariation);
-                self.recompute_board();
+                self.sync_board();
             }
             Message::NavigateLastVariation => {
                 self.editor.apply(EditCommand::NavigateLastVariation);
-                self.recompute_board();
+                self.sync_board();
             }
             Message::NavigateFirst => {
                 self.editor.apply(EditCommand::NavigateFirst);
-                self.recompute_board();
+                self.sync_board();
             }
             Message::NavigateLast => {
                 self.editor.apply(EditCommand::NavigateLast);
-                self.recompute_board();
+                self.sync_board();
             }
             Message::NavigateBranch(n) => {
                 let old_cursor = self.editor.cursor;
                 self.editor.apply(EditCommand::NavigateBranch(n));
                 if self.editor.cursor != old_cursor {
-                    self.board_history.push(self.cached_board.clone());
-                    self.cached_board
-                        .apply_node(self.editor.tree.node(self.editor.cursor));
+                    self.sync_board();
                 }
             }
             Message::NavigateToNode(id) => {
                 self.editor.apply(EditCommand::NavigateToNode(id));
-                self.recompute_board();
+                self.sync_board();
             }
             Message::UndoRequested => {
                 self.editor.apply(EditCommand::Undo);
@@ -353,7 +338,7 @@ impl GuiApp {
                 if let Some(&root) = self.editor.tree.roots.get(n) {
                     self.active_game_index = n;
                     self.editor.apply(EditCommand::NavigateToNode(root));
-                    self.recompute_board();
+                    self.sync_board();
                 }
             }
             Message::DismissStatus => {
@@ -372,7 +357,7 @@ impl GuiApp {
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        let board = &self.cached_board;
+        let board = self.board_cursor.board();
         let player = current_player(board);
 
         // Compute last move coord from the current cursor node
@@ -702,15 +687,9 @@ fn extract_game_info(tree: &GameTree, root: NodeId) -> GameInfo {
 }
 
 fn new_game_tree() -> GameTree {
-    use tesuji::sgf::node::{FileFormat, GameType, Komi};
-    let mut tree = GameTree::new();
-    tree.node_mut(tree.roots[0]).properties = vec![
-        SGFProperty::GM(GameType::Go),
-        SGFProperty::FF(FileFormat::FF4),
-        SGFProperty::SZ(19),
-        SGFProperty::KM(Komi::default()),
-    ];
-    tree
+    GameSetup::new()
+        .build()
+        .expect("the default game setup is valid")
 }
 
 fn last_move_coord(editor: &Editor) -> Option<(usize, usize)> {
@@ -730,54 +709,16 @@ fn last_move_coord(editor: &Editor) -> Option<(usize, usize)> {
     None
 }
 
-/// Build a board snapshot *and* a history stack for every position along
-/// the root→cursor path. The history allows O(1) backward navigation.
-fn board_with_history(tree: &GameTree, cursor: NodeId) -> (Board, Vec<Board>) {
-    let mut path: Vec<NodeId> = Vec::new();
-    let mut current = cursor;
-    loop {
-        path.push(current);
-        match tree.node(current).parent {
-            Some(parent) => current = parent,
-            None => break,
-        }
-    }
-    path.reverse();
-
-    let mut board = Board::from_tree(tree, path[0]); // root node
-    let mut history: Vec<Board> = Vec::with_capacity(path.len().saturating_sub(1));
-
-    for &id in &path[1..] {
-        history.push(board.clone());
-        board.apply_node(tree.node(id));
-    }
-
-    (board, history)
-}
-
 /// Returns the SGFProperty for placing a stone at (col, row), or an error.
 fn try_place_stone(app: &GuiApp, col: usize, row: usize) -> Result<SGFProperty, String> {
-    let board = &app.cached_board;
-    let size = board.size;
-
-    if col >= size || row >= size {
-        return Err(format!("({col},{row}) is off the board"));
-    }
-
-    if board.cells[row][col] != Cell::Empty {
-        return Err("Intersection is occupied".into());
-    }
-
-    if board.ko_point == Some((row, col)) {
-        return Err("Illegal move: ko".into());
-    }
-
+    let board = app.board_cursor.board();
     let color = current_player(board);
-    if would_be_suicide(board, col, row, color) {
-        return Err("Illegal move: suicide".into());
-    }
-
     let coord = GoCoord::from_colrow(col, row);
+    board
+        .clone()
+        .play(color, coord)
+        .map_err(|e| format!("Illegal move: {e}"))?;
+
     let prop = match color {
         Cell::Black => SGFProperty::B(coord),
         Cell::White => SGFProperty::W(coord),
@@ -785,43 +726,3 @@ fn try_place_stone(app: &GuiApp, col: usize, row: usize) -> Result<SGFProperty,
     };
     Ok(prop)
 }
-
-/// Simulate placing `color` at `(col, row)` and check if it would be a suicide.
-/// Returns true if the group formed would have zero liberties after captures.
-fn would_be_suicide(board: &Board, col: usize, row: usize, color: Cell) -> bool {
-    let mut sim_cells = board.cells;
-    let size = board.size;
-
-    sim_cells[row][col] = color;
-
-    let opponent = match color {
-        Cell::Black => Cell::White,
-        Cell::White => Cell::Black,
-        Cell::Empty => return false,
-    };
-
-    // Track which opponent cells have already been checked to avoid
-    // re-walking the same group when multiple neighbors belong to it.
-    let mut checked = [[false; 19]; 19];
-
-    let nbrs = orthogonal_neighbors(row, col, size);
-    for &(nr, nc) in nbrs.as_slice() {
-        if sim_cells[nr][nc] != opponent || checked[nr][nc] {
-            continue;
-        }
-        let group = find_group(&sim_cells, nr, nc, size);
-        // Mark all group members as checked
-        for &(gr, gc) in &group {
-            checked[gr][gc] = true;
-        }
-        if count_liberties(&sim_cells, &group, size) == 0 {
-            for (gr, gc) in group {
-                sim_cells[gr][gc] = Cell::Empty;
-            }
-        }
-    }
-
-    // After captures, check if the placed group has any liberties
-    let placed_group = find_group(&sim_cells, row, col, size);
-    count_liberties(&sim_cells, &placed_group, size) == 0
-}
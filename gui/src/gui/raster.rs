@@ -0,0 +1,394 @@
+//! Headless PNG export of board positions.
+//!
+//! Replays the [`DrawPrimitive`] list built for the canvas onto a CPU pixel
+//! buffer, so thumbnails and chat-bot images can be produced on machines
+//! without a GPU or a window.  Coordinate labels are drawn with a small
+//! built-in bitmap font since no text shaper is available headless.
+
+use iced::{Color, Size, widget::image};
+use tiny_skia::{
+    FillRule, FilterQuality, GradientStop, LinearGradient, Paint, PathBuilder, Pattern, Pixmap,
+    Rect, Shader, SpreadMode, Stroke, Transform,
+};
+
+use crate::gui::{
+    assets::BoardAssets,
+    board::{BoardMetrics, DrawPrimitive, build_board_primitives, build_board_primitives_textured},
+};
+use tesuji::sgf::Board;
+
+/// Glyph cell of the label font: 5 columns plus 1 column of spacing, 7 rows.
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_ADVANCE: f32 = 6.0;
+const GLYPH_HEIGHT: usize = 7;
+
+/// Font pixels per unit of text size; a size-10 label is 7 pixels tall.
+const GLYPH_SCALE: f32 = 0.1;
+
+/// Render `board` as a `size_px`×`size_px` PNG.
+///
+/// `last_move` is `(col, row)`, as for [`build_board_primitives`].  With
+/// `assets` the wood and stone textures are used, otherwise the flat vector
+/// style.
+pub fn render_png(
+    board: &Board,
+    size_px: u32,
+    last_move: Option<(usize, usize)>,
+    assets: Option<&BoardAssets>,
+) -> Result<Vec<u8>, String> {
+    let bounds = Size {
+        width: size_px as f32,
+        height: size_px as f32,
+    };
+    let metrics = BoardMetrics::new(bounds, board.size);
+    let mut raster = Raster::new(size_px, size_px)?;
+    match assets {
+        Some(assets) => {
+            let layers = build_board_primitives_textured(board, &metrics, None, last_move, assets);
+            raster.draw(&layers.background);
+            raster.draw_all(&layers.grid);
+            raster.draw_all(&layers.shadows);
+            raster.draw_all(&layers.stones);
+            raster.draw_all(&layers.overlays);
+        }
+        None => raster.draw_all(&build_board_primitives(board, &metrics, None, last_move)),
+    }
+    raster.encode_png()
+}
+
+/// A CPU pixel buffer that [`DrawPrimitive`]s are replayed onto.
+///
+/// Image handles are decoded the first time they are drawn and kept for
+/// later primitives; handles that cannot be decoded are skipped.
+pub struct Raster {
+    pixmap: Pixmap,
+    images: Vec<(image::Handle, Option<Pixmap>)>,
+}
+
+impl Raster {
+    /// A transparent `width`×`height` buffer.
+    pub fn new(width: u32, height: u32) -> Result<Self, String> {
+        let pixmap = Pixmap::new(width, height)
+            .ok_or_else(|| format!("Invalid image size {width}x{height}"))?;
+        Ok(Self {
+            pixmap,
+            images: Vec::new(),
+        })
+    }
+
+    pub fn draw_all(&mut self, primitives: &[DrawPrimitive]) {
+        for prim in primitives {
+            self.draw(prim);
+        }
+    }
+
+    pub fn draw(&mut self, prim: &DrawPrimitive) {
+        let identity = Transform::identity();
+        match prim {
+            DrawPrimitive::FillRect {
+                origin,
+                size,
+                color,
+            } => {
+                if let Some(rect) = Rect::from_xywh(origin.x, origin.y, size.width, size.height) {
+                    self.pixmap.fill_rect(rect, &solid(*color), identity, None);
+                }
+            }
+            DrawPrimitive::StrokeLine {
+                from,
+                to,
+                color,
+                width,
+            } => {
+                let mut pb = PathBuilder::new();
+                pb.move_to(from.x, from.y);
+                pb.line_to(to.x, to.y);
+                if let Some(path) = pb.finish() {
+                    self.pixmap
+                        .stroke_path(&path, &solid(*color), &stroke(*width), identity, None);
+                }
+            }
+            DrawPrimitive::FillCircle {
+                center,
+                radius,
+                color,
+            } => {
+                if let Some(path) = PathBuilder::from_circle(center.x, center.y, *radius) {
+                    self.pixmap
+                        .fill_path(&path, &solid(*color), FillRule::Winding, identity, None);
+                }
+            }
+            DrawPrimitive::FillCircleGradient {
+                center,
+                radius,
+                gradient,
+            } => {
+                let stops = gradient
+                    .stops
+                    .iter()
+                    .map(|stop| GradientStop::new(stop.offset, skia_color(stop.color)))
+                    .collect();
+                let shader = LinearGradient::new(
+                    tiny_skia::Point::from_xy(gradient.start.x, gradient.start.y),
+                    tiny_skia::Point::from_xy(gradient.end.x, gradient.end.y),
+                    stops,
+                    SpreadMode::Pad,
+                    identity,
+                );
+                if let (Some(shader), Some(path)) = (
+                    shader,
+                    PathBuilder::from_circle(center.x, center.y, *radius),
+                ) {
+                    let paint = Paint {
+                        shader,
+                        anti_alias: true,
+                        ..Paint::default()
+                    };
+                    self.pixmap
+                        .fill_path(&path, &paint, FillRule::Winding, identity, None);
+                }
+            }
+            DrawPrimitive::StrokeCircle {
+                center,
+                radius,
+                color,
+                width,
+            } => {
+                if let Some(path) = PathBuilder::from_circle(center.x, center.y, *radius) {
+                    self.pixmap
+                        .stroke_path(&path, &solid(*color), &stroke(*width), identity, None);
+                }
+            }
+            DrawPrimitive::StrokeRect {
+                origin,
+                size,
+                color,
+                width,
+            } => {
+                if let Some(rect) = Rect::from_xywh(origin.x, origin.y, size.width, size.height) {
+                    let path = PathBuilder::from_rect(rect);
+                    self.pixmap
+                        .stroke_path(&path, &solid(*color), &stroke(*width), identity, None);
+                }
+            }
+            DrawPrimitive::DrawImage { bounds, handle } => {
+                let index = self.image_index(handle);
+                let Some(texture) = &self.images[index].1 else {
+                    return;
+                };
+                let Some(rect) = Rect::from_xywh(bounds.x, bounds.y, bounds.width, bounds.height)
+                else {
+                    return;
+                };
+                // Scale the texture onto the bounds, as the canvas does.
+                let transform = Transform::from_row(
+                    bounds.width / texture.width() as f32,
+                    0.0,
+                    0.0,
+                    bounds.height / texture.height() as f32,
+                    bounds.x,
+                    bounds.y,
+                );
+                let paint = Paint {
+                    shader: Pattern::new(
+                        texture.as_ref(),
+                        SpreadMode::Pad,
+                        FilterQuality::Bilinear,
+                        1.0,
+                        transform,
+                    ),
+                    anti_alias: true,
+                    ..Paint::default()
+                };
+                self.pixmap.fill_rect(rect, &paint, identity, None);
+            }
+            DrawPrimitive::DrawText {
+                content,
+                position,
+                size,
+                color,
+            } => {
+                self.draw_text(content, position.x, position.y, *size, *color);
+            }
+        }
+    }
+
+    /// The buffer encoded as a PNG file.
+    pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+        self.pixmap.encode_png().map_err(|e| e.to_string())
+    }
+
+    /// The decoded texture slot for `handle`, decoding it on first use.
+    fn image_index(&mut self, handle: &image::Handle) -> usize {
+        if let Some(index) = self.images.iter().position(|(h, _)| h == handle) {
+            return index;
+        }
+        self.images.push((handle.clone(), decode(handle)));
+        self.images.len() - 1
+    }
+
+    /// Draw `content` centred on `(x, y)`, matching the canvas alignment.
+    fn draw_text(&mut self, content: &str, x: f32, y: f32, size: f32, color: Color) {
+        let scale = size * GLYPH_SCALE;
+        let chars = content.chars().count();
+        if chars == 0 {
+            return;
+        }
+        let width = (chars as f32 * GLYPH_ADVANCE - 1.0) * scale;
+        let left = x - width / 2.0;
+        let top = y - GLYPH_HEIGHT as f32 * scale / 2.0;
+        // One path per label: tiny-skia's anti-aliased `fill_rect` asserts on
+        // rectangles this small.
+        let mut pb = PathBuilder::new();
+        for (i, c) in content.chars().enumerate() {
+            let Some(rows) = glyph(c) else {
+                continue;
+            };
+            let glyph_left = left + i as f32 * GLYPH_ADVANCE * scale;
+            for (r, bits) in rows.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                        continue;
+                    }
+                    if let Some(rect) = Rect::from_xywh(
+                        glyph_left + col as f32 * scale,
+                        top + r as f32 * scale,
+                        scale,
+                        scale,
+                    ) {
+                        pb.push_rect(rect);
+                    }
+                }
+            }
+        }
+        if let Some(path) = pb.finish() {
+            self.pixmap.fill_path(
+                &path,
+                &solid(color),
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+}
+
+fn skia_color(color: Color) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba(color.r, color.g, color.b, color.a)
+        .unwrap_or(tiny_skia::Color::BLACK)
+}
+
+fn solid(color: Color) -> Paint<'static> {
+    Paint {
+        shader: Shader::SolidColor(skia_color(color)),
+        anti_alias: true,
+        ..Paint::default()
+    }
+}
+
+fn stroke(width: f32) -> Stroke {
+    Stroke {
+        width,
+        ..Stroke::default()
+    }
+}
+
+/// Decode the pixels behind an image handle.  Only PNG data is supported
+/// for paths and encoded bytes, which covers the files in `assets/`.
+fn decode(handle: &image::Handle) -> Option<Pixmap> {
+    match handle {
+        image::Handle::Path(_, path) => Pixmap::decode_png(&std::fs::read(path).ok()?).ok(),
+        image::Handle::Bytes(_, bytes) => Pixmap::decode_png(bytes).ok(),
+        image::Handle::Rgba {
+            width,
+            height,
+            pixels,
+            ..
+        } => {
+            let mut data = pixels.to_vec();
+            // tiny-skia stores premultiplied alpha.
+            for px in data.chunks_exact_mut(4) {
+                let a = u16::from(px[3]);
+                for channel in &mut px[..3] {
+                    *channel = ((u16::from(*channel) * a + 127) / 255) as u8;
+                }
+            }
+            Pixmap::from_vec(data, tiny_skia::IntSize::from_wh(*width, *height)?)
+        }
+    }
+}
+
+/// Rows of a 5×7 glyph, most significant of the low five bits leftmost.
+/// Covers the characters used by coordinate labels.
+fn glyph(c: char) -> Option<[u8; GLYPH_HEIGHT]> {
+    Some(match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tesuji::sgf::Cell;
+
+    #[test]
+    fn stones_show_against_the_board() {
+        let png = render_png(&Board::new(), 64, None, None).unwrap();
+        let empty = Pixmap::decode_png(&png).unwrap();
+        assert_eq!((empty.width(), empty.height()), (64, 64));
+
+        let mut board = Board::new();
+        board.cells[9][9] = Cell::Black;
+        board.cells[3][15] = Cell::White;
+        let png = render_png(&board, 64, None, None).unwrap();
+        let stones = Pixmap::decode_png(&png).unwrap();
+
+        let metrics = BoardMetrics::new(
+            Size {
+                width: 64.0,
+                height: 64.0,
+            },
+            19,
+        );
+        let at = |i: usize| {
+            (metrics.origin.x + metrics.margin + i as f32 * metrics.cell_size).round() as u32
+        };
+        let mid = (at(6) + at(7)) / 2;
+        let background = stones.pixel(mid, mid).unwrap();
+        for (row, col) in [(9, 9), (3, 15)] {
+            let stone = stones.pixel(at(col), at(row)).unwrap();
+            assert_ne!(stone, background);
+            assert_ne!(stone, empty.pixel(at(col), at(row)).unwrap());
+        }
+    }
+}
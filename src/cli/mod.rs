@@ -9,7 +9,10 @@ use clap_complete::{Shell, generate};
 
 use crate::{
     editor::{Adapter, EditCommand, Editor, run_editor},
-    sgf::{GameTree, write_sgf},
+    render::{TextDiagram, TextStyle},
+    sgf::{
+        Board, Cell, GameTree, NodePath, Query, diff_trees, node::GoCoord, parse_sgf, write_sgf,
+    },
 };
 
 // ---------------------------------------------------------------------------
@@ -101,6 +104,72 @@ fn repl_command() -> Command {
                         .value_parser(clap::value_parser!(usize)),
                 ),
         )
+        .subcommand(
+            Command::new("goto")
+                .visible_alias("g")
+                .about("Jump to the node at PATH")
+                .long_about(
+                    "Move the cursor to the node addressed by PATH, written as \
+                     GAME:I.J.K… with 0-based indices (e.g. `1:0.0.2.0`). The \
+                     current node's path is shown in its header.",
+                )
+                .arg(
+                    Arg::new("path")
+                        .value_name("PATH")
+                        .help("Node path, e.g. 0:0.1.0")
+                        .required(true)
+                        .value_parser(clap::value_parser!(NodePath)),
+                ),
+        )
+        .subcommand(
+            Command::new("find")
+                .visible_alias("f")
+                .about("Jump to the next node matching all given conditions")
+                .long_about(
+                    "Search every game record for nodes that satisfy all of the \
+                     given conditions, print how many match, and move the cursor \
+                     to the next match after the current node (wrapping around). \
+                     Repeat the command to step through the matches.",
+                )
+                .arg(
+                    Arg::new("comment")
+                        .long("comment")
+                        .value_name("TEXT")
+                        .help("Comment contains TEXT"),
+                )
+                .arg(
+                    Arg::new("regex")
+                        .long("regex")
+                        .value_name("PATTERN")
+                        .help("Comment matches the regular expression PATTERN"),
+                )
+                .arg(
+                    Arg::new("at")
+                        .long("at")
+                        .value_name("COORD")
+                        .help("Move played at the SGF coordinate COORD (e.g. pd)")
+                        .value_parser(clap::value_parser!(GoCoord)),
+                )
+                .arg(
+                    Arg::new("black")
+                        .long("black")
+                        .help("Move played by black")
+                        .action(clap::ArgAction::SetTrue)
+                        .conflicts_with("white"),
+                )
+                .arg(
+                    Arg::new("white")
+                        .long("white")
+                        .help("Move played by white")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("prop")
+                        .long("prop")
+                        .value_name("TAG")
+                        .help("Node carries a property with SGF tag TAG (e.g. BM)"),
+                ),
+        )
         // ── Display ─────────────────────────────────────────────────────────
         .subcommand(
             Command::new("show")
@@ -116,6 +185,35 @@ fn repl_command() -> Command {
                      with `*`. Each line shows the node ID and its SGF properties.",
                 ),
         )
+        .subcommand(
+            Command::new("board")
+                .visible_alias("bd")
+                .about("Draw the board at the current node")
+                .long_about(
+                    "Print a text diagram of the position at the current node, \
+                     with the last move in brackets, the ko point and the \
+                     node's markup.",
+                )
+                .arg(
+                    Arg::new("style")
+                        .long("style")
+                        .value_name("STYLE")
+                        .help("Character set: ascii, unicode or ansi (colour)")
+                        .value_parser(["ascii", "unicode", "ansi"])
+                        .default_value("ascii"),
+                ),
+        )
+        .subcommand(
+            Command::new("estimate")
+                .visible_alias("e")
+                .about("Estimate territory and the score at the current node")
+                .long_about(
+                    "Print a quick influence-based ownership map of the current \
+                     position and the projected result under the game's rules. \
+                     `X`/`O` are live stones, `x`/`o` stones judged dead, and \
+                     `+`/`-` black/white area.",
+                ),
+        )
         // ── File I/O ────────────────────────────────────────────────────────
         .subcommand(
             Command::new("load")
@@ -144,6 +242,22 @@ fn repl_command() -> Command {
                         .required(false),
                 ),
         )
+        .subcommand(
+            Command::new("diff")
+                .about("Show what changed relative to an SGF file on disk")
+                .long_about(
+                    "Compare the file at PATH with the current game tree and \
+                     print added or removed variations, changed properties and \
+                     changed comments. If PATH is omitted the file opened on \
+                     startup (or via `load`) is used.",
+                )
+                .arg(
+                    Arg::new("path")
+                        .value_name("PATH")
+                        .help("File to compare against (defaults to the currently loaded file)")
+                        .required(false),
+                ),
+        )
         // ── Editing ─────────────────────────────────────────────────────────
         .subcommand(
             Command::new("delete")
@@ -237,7 +351,10 @@ impl CliAdapter {
 impl Adapter for CliAdapter {
     fn render(&mut self, editor: &Editor) -> Result<()> {
         let node = editor.tree.node(editor.cursor);
-        let mut display = format!("--- node {} ---\n", editor.cursor);
+        let mut display = match editor.tree.node_path(editor.cursor) {
+            Some(path) => format!("--- node {} ({path}) ---\n", editor.cursor),
+            None => format!("--- node {} ---\n", editor.cursor),
+        };
         for prop in &node.properties {
             display.push_str(&format!("  {prop}\n"));
         }
@@ -248,7 +365,7 @@ impl Adapter for CliAdapter {
         Ok(())
     }
 
-    fn next_command(&mut self) -> Result<Option<EditCommand>> {
+    fn next_command(&mut self, editor: &Editor) -> Result<Option<EditCommand>> {
         let stdin = io::stdin();
 
         loop {
@@ -284,6 +401,48 @@ impl Adapter for CliAdapter {
                         let n = *m.get_one::<usize>("n").unwrap();
                         return Ok(Some(EditCommand::NavigateBranch(n)));
                     }
+                    Some(("goto", m)) => {
+                        let path = m.get_one::<NodePath>("path").unwrap().clone();
+                        return Ok(Some(EditCommand::NavigateToPath(path)));
+                    }
+                    Some(("find", m)) => {
+                        let mut query = Query::new();
+                        if let Some(text) = m.get_one::<String>("comment") {
+                            query = query.comment_contains(text);
+                        }
+                        if let Some(pattern) = m.get_one::<String>("regex") {
+                            query = match query.comment_matches(pattern) {
+                                Ok(q) => q,
+                                Err(e) => {
+                                    eprintln!("error: {e:#}");
+                                    continue;
+                                }
+                            };
+                        }
+                        if let Some(&coord) = m.get_one::<GoCoord>("at") {
+                            query = query.move_at(coord);
+                        }
+                        if m.get_flag("black") {
+                            query = query.player(Cell::Black);
+                        }
+                        if m.get_flag("white") {
+                            query = query.player(Cell::White);
+                        }
+                        if let Some(tag) = m.get_one::<String>("prop") {
+                            query = query.has_property(tag);
+                        }
+                        let hits = query.run(&editor.tree);
+                        match Query::next_hit(&editor.tree, &hits, editor.cursor) {
+                            Some(id) => {
+                                println!("{} matching node(s)", hits.len());
+                                return Ok(Some(EditCommand::NavigateToNode(id)));
+                            }
+                            None => {
+                                println!("no matching nodes");
+                                continue;
+                            }
+                        }
+                    }
                     // ── Display ─────────────────────────────────────────────
                     Some(("show", _)) => {
                         print!("{}", self.last_node_display);
@@ -293,11 +452,37 @@ impl Adapter for CliAdapter {
                         print!("{}", self.last_tree_display);
                         continue;
                     }
+                    Some(("board", m)) => {
+                        let style = match m.get_one::<String>("style").map(String::as_str) {
+                            Some("unicode") => TextStyle::Unicode,
+                            Some("ansi") => TextStyle::Ansi,
+                            _ => TextStyle::Ascii,
+                        };
+                        let board = Board::from_tree(&editor.tree, editor.cursor);
+                        let diagram = TextDiagram::new(&board)
+                            .style(style)
+                            .node(editor.tree.node(editor.cursor));
+                        print!("{diagram}");
+                        continue;
+                    }
+                    Some(("estimate", _)) => {
+                        let board = Board::from_tree(&editor.tree, editor.cursor);
+                        let estimate = board.estimate();
+                        print!("{estimate}");
+                        println!(
+                            "{} (B {} / W {}, {} rules)",
+                            estimate.result(),
+                            estimate.black,
+                            estimate.white,
+                            board.rules
+                        );
+                        continue;
+                    }
                     // ── File I/O ────────────────────────────────────────────
                     Some(("load", m)) => {
                         let path = m.get_one::<String>("path").unwrap();
                         let content = std::fs::read_to_string(path)?;
-                        let tree = crate::sgf::parse_sgf(&content)?;
+                        let tree = parse_sgf(&content)?;
                         self.file_path = Some(PathBuf::from(path));
                         return Ok(Some(EditCommand::Load(tree)));
                     }
@@ -319,6 +504,39 @@ impl Adapter for CliAdapter {
                         println!("saved to {}", path.display());
                         continue;
                     }
+                    Some(("diff", m)) => {
+                        let path = match m.get_one::<String>("path") {
+                            Some(p) => PathBuf::from(p),
+                            None => match &self.file_path {
+                                Some(p) => p.clone(),
+                                None => {
+                                    eprintln!(
+                                        "error: no file is currently open; \
+                                         usage: diff <PATH>"
+                                    );
+                                    continue;
+                                }
+                            },
+                        };
+                        // A bad file must not end the session and lose the edits.
+                        let old = match std::fs::read_to_string(&path)
+                            .map_err(anyhow::Error::from)
+                            .and_then(|text| parse_sgf(&text))
+                        {
+                            Ok(tree) => tree,
+                            Err(e) => {
+                                eprintln!("error: {}: {e:#}", path.display());
+                                continue;
+                            }
+                        };
+                        let diff = diff_trees(&old, &editor.tree);
+                        if diff.is_empty() {
+                            println!("no changes relative to {}", path.display());
+                        } else {
+                            print!("{diff}");
+                        }
+                        continue;
+                    }
                     // ── Editing ─────────────────────────────────────────────
                     Some(("delete", _)) => return Ok(Some(EditCommand::DeleteCurrentNode)),
                     Some(("variation", _)) => return Ok(Some(EditCommand::AppendVariation)),
@@ -349,7 +567,7 @@ impl Adapter for CliAdapter {
 pub fn run(file: Option<&str>) -> Result<()> {
     let (tree, file_path) = if let Some(path) = file {
         let content = std::fs::read_to_string(path)?;
-        let tree = crate::sgf::parse_sgf(&content)?;
+        let tree = parse_sgf(&content)?;
         (tree, Some(PathBuf::from(path)))
     } else {
         (GameTree::new(), None)
@@ -1,4 +1,7 @@
-use crate::sgf::{GameTree, MainlineIter, NodeId, SubtreeIter, TreeNode};
+use crate::sgf::{
+    AncestorsIter, BreadthFirstIter, DepthIter, GameTree, LeavesIter, MainlineIter, NodeId,
+    PostOrderIter, SiblingsIter, SubtreeIter, TreeNode, VariationsIter,
+};
 
 /// A lightweight cursor into a borrowed [`GameTree`].
 ///
@@ -64,4 +67,32 @@ impl<'a> TreeCursor<'a> {
     pub fn iter_subtree(&self) -> SubtreeIter<'a> {
         self.tree.iter_subtree(self.current)
     }
+
+    pub fn iter_ancestors(&self) -> AncestorsIter<'a> {
+        self.tree.iter_ancestors(self.current)
+    }
+
+    pub fn iter_leaves(&self) -> LeavesIter<'a> {
+        self.tree.iter_leaves(self.current)
+    }
+
+    pub fn iter_variations(&self) -> VariationsIter<'a> {
+        self.tree.iter_variations(self.current)
+    }
+
+    pub fn iter_breadth_first(&self) -> BreadthFirstIter<'a> {
+        self.tree.iter_breadth_first(self.current)
+    }
+
+    pub fn iter_post_order(&self) -> PostOrderIter<'a> {
+        self.tree.iter_post_order(self.current)
+    }
+
+    pub fn iter_siblings(&self) -> SiblingsIter<'a> {
+        self.tree.iter_siblings(self.current)
+    }
+
+    pub fn iter_with_depth(&self) -> DepthIter<'a> {
+        self.tree.iter_with_depth(self.current)
+    }
 }
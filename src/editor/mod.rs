@@ -11,7 +11,10 @@
 mod cursor;
 pub use cursor::TreeCursor;
 
-use crate::sgf::{GameTree, NodeId, SGFProperty};
+use crate::sgf::{
+    Board, Cell, GameTree, IllegalMove, MoveOutcome, NodeId, NodePath, SGFProperty, Transpositions,
+    node::GoCoord,
+};
 
 /// An SGF game-tree editor with undo/redo support.
 ///
@@ -31,6 +34,11 @@ pub struct Editor {
 pub enum EditCommand {
     /// Append a new child node containing `prop` and move the cursor to it.
     AddMove(SGFProperty),
+    /// Like [`AddMove`](EditCommand::AddMove), but only if `color` may legally
+    /// play at `coord` in the position at the cursor.  Illegal moves are
+    /// ignored and not recorded on the undo stack; use [`Editor::play_move`]
+    /// to learn why a move was rejected.
+    PlayMove { color: Cell, coord: GoCoord },
     /// Insert or replace a property on the current node (keyed by SGF tag).
     SetProperty(SGFProperty),
     /// Remove the property with the given SGF tag from the current node.
@@ -40,6 +48,23 @@ pub enum EditCommand {
     DeleteCurrentNode,
     /// Add an empty child node at the current position without moving the cursor.
     AppendVariation,
+    /// Make the current node part of the main line by moving it and each of
+    /// its ancestors to the front of their sibling lists.
+    PromoteVariation,
+    /// Move the current node to position `n` among its siblings (0-indexed,
+    /// clamped to the last position).
+    ReorderVariation(usize),
+    /// Detach the current node's subtree and re-attach it as the last child of
+    /// the given node.  No-op if the target lies inside the subtree or the
+    /// current node is a game root.
+    MoveSubtree(NodeId),
+    /// Copy the current node's subtree and append it as the last child of the
+    /// given node, which may belong to another game record.  The cursor does
+    /// not move.
+    CopySubtree(NodeId),
+    /// Copy the subtree rooted at `node` in another tree and append it as the
+    /// last child of the current node.  The cursor does not move.
+    GraftSubtree { source: GameTree, node: NodeId },
     /// Move the cursor to the first child of the current node, if one exists.
     NavigateNext,
     /// Move the cursor to the parent of the current node, if one exists.
@@ -61,6 +86,15 @@ pub enum EditCommand {
     NavigateLast,
     /// Move the cursor directly to the given [`NodeId`].
     NavigateToNode(NodeId),
+    /// Move the cursor to the node addressed by a [`NodePath`].  No-op if the
+    /// path does not resolve in the current tree.
+    NavigateToPath(NodePath),
+    /// Move the cursor to the next node (in DFS pre-order, wrapping around)
+    /// that reaches the same position as the current node.
+    NavigateNextTransposition,
+    /// Like [`NavigateNextTransposition`](EditCommand::NavigateNextTransposition)
+    /// but moves to the previous transposition.
+    NavigatePrevTransposition,
     /// Replace the entire tree (e.g. after loading a new file).
     Load(GameTree),
     /// Undo the most recent mutating command.
@@ -69,30 +103,6 @@ pub enum EditCommand {
     Redo,
 }
 
-fn property_key(p: &SGFProperty) -> &str {
-    match p {
-        SGFProperty::AP(_) => "AP",
-        SGFProperty::B(_) => "B",
-        SGFProperty::W(_) => "W",
-        SGFProperty::AB(_) => "AB",
-        SGFProperty::AW(_) => "AW",
-        SGFProperty::CA(_) => "CA",
-        SGFProperty::DT(_) => "DT",
-        SGFProperty::FF(_) => "FF",
-        SGFProperty::GM(_) => "GM",
-        SGFProperty::KM(_) => "KM",
-        SGFProperty::SZ(_) => "SZ",
-        SGFProperty::PB(_) => "PB",
-        SGFProperty::PW(_) => "PW",
-        SGFProperty::BR(_) => "BR",
-        SGFProperty::WR(_) => "WR",
-        SGFProperty::HA(_) => "HA",
-        SGFProperty::RE(_) => "RE",
-        SGFProperty::C(_) => "C",
-        SGFProperty::Unknown(k, _) => k.as_str(),
-    }
-}
-
 impl Editor {
     /// Create a new editor from a parsed [`GameTree`].
     ///
@@ -109,9 +119,13 @@ impl Editor {
 
     /// Apply a command to the editor.
     ///
-    /// Mutating commands ([`EditCommand::AddMove`], [`EditCommand::SetProperty`],
+    /// Mutating commands ([`EditCommand::AddMove`], a legal
+    /// [`EditCommand::PlayMove`], [`EditCommand::SetProperty`],
     /// [`EditCommand::RemoveProperty`], [`EditCommand::DeleteCurrentNode`],
-    /// [`EditCommand::AppendVariation`]) snapshot the current state onto the
+    /// [`EditCommand::AppendVariation`] and the structural commands
+    /// [`EditCommand::PromoteVariation`], [`EditCommand::ReorderVariation`],
+    /// a valid [`EditCommand::MoveSubtree`], [`EditCommand::CopySubtree`],
+    /// [`EditCommand::GraftSubtree`]) snapshot the current state onto the
     /// undo stack and clear the redo stack before executing.
     pub fn apply(&mut self, cmd: EditCommand) {
         let is_mutating = matches!(
@@ -121,6 +135,10 @@ impl Editor {
                 | EditCommand::RemoveProperty(_)
                 | EditCommand::DeleteCurrentNode
                 | EditCommand::AppendVariation
+                | EditCommand::PromoteVariation
+                | EditCommand::ReorderVariation(_)
+                | EditCommand::CopySubtree(_)
+                | EditCommand::GraftSubtree { .. }
         );
         if is_mutating {
             self.undo_stack.push((self.tree.clone(), self.cursor));
@@ -132,11 +150,15 @@ impl Editor {
                 let id = self.tree.add_node(self.cursor, vec![prop]);
                 self.cursor = id;
             }
+            EditCommand::PlayMove { color, coord } => {
+                // `play_move` adds the move through `AddMove`, which takes the
+                // snapshot; an illegal move changes nothing.
+                let _ = self.play_move(color, coord);
+            }
             EditCommand::SetProperty(prop) => {
-                let key = property_key(&prop).to_string();
+                let key = prop.tag().to_string();
                 let node = self.tree.node_mut(self.cursor);
-                if let Some(existing) = node.properties.iter_mut().find(|p| property_key(p) == key)
-                {
+                if let Some(existing) = node.properties.iter_mut().find(|p| p.tag() == key) {
                     *existing = prop;
                 } else {
                     node.properties.push(prop);
@@ -146,7 +168,7 @@ impl Editor {
                 self.tree
                     .node_mut(self.cursor)
                     .properties
-                    .retain(|p| property_key(p) != key);
+                    .retain(|p| p.tag() != key);
             }
             EditCommand::DeleteCurrentNode => {
                 let old_cursor = self.cursor;
@@ -158,6 +180,26 @@ impl Editor {
             EditCommand::AppendVariation => {
                 self.tree.add_node(self.cursor, vec![]);
             }
+            EditCommand::PromoteVariation => {
+                self.tree.promote_variation(self.cursor);
+            }
+            EditCommand::ReorderVariation(n) => {
+                self.tree.reorder_variation(self.cursor, n);
+            }
+            EditCommand::MoveSubtree(new_parent) => {
+                // An invalid target leaves the tree and both stacks untouched.
+                let snapshot = (self.tree.clone(), self.cursor);
+                if self.tree.move_subtree(self.cursor, new_parent).is_ok() {
+                    self.undo_stack.push(snapshot);
+                    self.redo_stack.clear();
+                }
+            }
+            EditCommand::CopySubtree(new_parent) => {
+                self.tree.copy_subtree(self.cursor, new_parent);
+            }
+            EditCommand::GraftSubtree { source, node } => {
+                self.tree.graft_subtree(&source, node, self.cursor);
+            }
             EditCommand::NavigateNext => {
                 if let Some(&c) = self.tree.node(self.cursor).children.first() {
                     self.cursor = c;
@@ -198,6 +240,17 @@ impl Editor {
             EditCommand::NavigateToNode(id) => {
                 self.cursor = id;
             }
+            EditCommand::NavigateToPath(path) => {
+                if let Some(id) = self.tree.node_at(&path) {
+                    self.cursor = id;
+                }
+            }
+            EditCommand::NavigateNextTransposition => {
+                self.switch_transposition(|idx, len| (idx + 1) % len);
+            }
+            EditCommand::NavigatePrevTransposition => {
+                self.switch_transposition(|idx, len| (idx + len - 1) % len);
+            }
             EditCommand::Load(new_tree) => {
                 let cursor = new_tree.roots.first().copied().unwrap_or(0);
                 self.tree = new_tree;
@@ -222,6 +275,36 @@ impl Editor {
         }
     }
 
+    /// Add `color`'s move at `coord` as a new child of the cursor and move
+    /// the cursor to it, after checking legality against the position at the
+    /// cursor (see [`Board::play`]).  On error the editor is unchanged.
+    pub fn play_move(&mut self, color: Cell, coord: GoCoord) -> Result<MoveOutcome, IllegalMove> {
+        let mut board = Board::from_tree(&self.tree, self.cursor);
+        let outcome = board.play(color, coord)?;
+        let prop = match color {
+            Cell::White => SGFProperty::W(coord),
+            _ => SGFProperty::B(coord),
+        };
+        self.apply(EditCommand::AddMove(prop));
+        Ok(outcome)
+    }
+
+    /// Every node that reaches the same position (stones and side to move)
+    /// as the cursor, including the cursor itself, in DFS pre-order.  Empty
+    /// if the cursor has no transposition or does not play a move.
+    pub fn transpositions(&self) -> Vec<NodeId> {
+        Transpositions::find(&self.tree).of(self.cursor).to_vec()
+    }
+
+    /// Move the cursor to the transposition at the index chosen by `pick`
+    /// from (current index, group size).
+    fn switch_transposition(&mut self, pick: impl Fn(usize, usize) -> usize) {
+        let group = self.transpositions();
+        if let Some(idx) = group.iter().position(|&id| id == self.cursor) {
+            self.cursor = group[pick(idx, group.len())];
+        }
+    }
+
     /// Walk up from cursor to the nearest branch point, pick a sibling via
     /// `pick`, then walk back down the same number of mainline steps.
     fn switch_variation(&mut self, pick: impl Fn(usize, usize) -> Option<usize>) {
@@ -257,10 +340,10 @@ impl Editor {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
-    use crate::sgf::node::GoCoord;
-    use crate::sgf::{GameTree, parse_sgf};
+    use crate::sgf::parse_sgf;
 
     fn simple_tree() -> Editor {
         let tree = parse_sgf("(;GM[1]FF[4]SZ[19];B[dd];W[pd])").unwrap();
@@ -379,6 +462,149 @@ mod tests {
         // Cursor should be on the new root.
         assert_eq!(ed.tree.node(ed.cursor).properties.len(), 2);
     }
+
+    #[test]
+    fn promote_variation_moves_branch_to_mainline() {
+        let tree = parse_sgf("(;GM[1];B[dd](;W[pd];B[pp])(;W[dp];B[pp]))").unwrap();
+        let mut ed = Editor::new(tree);
+        ed.apply(EditCommand::NavigateNext);
+        let b_node = ed.cursor;
+        ed.apply(EditCommand::NavigateBranch(1));
+        let second = ed.cursor;
+        ed.apply(EditCommand::NavigateNext);
+        ed.apply(EditCommand::PromoteVariation);
+        assert_eq!(ed.tree.node(b_node).children[0], second);
+        ed.apply(EditCommand::Undo);
+        assert_eq!(ed.tree.node(b_node).children[1], second);
+    }
+
+    #[test]
+    fn reorder_variation_clamps_index() {
+        let tree = parse_sgf("(;GM[1](;B[aa])(;B[bb])(;B[cc]))").unwrap();
+        let mut ed = Editor::new(tree);
+        let root = ed.cursor;
+        let first = ed.tree.node(root).children[0];
+        ed.apply(EditCommand::NavigateBranch(0));
+        ed.apply(EditCommand::ReorderVariation(99));
+        assert_eq!(ed.tree.node(root).children[2], first);
+        assert_eq!(ed.cursor, first);
+    }
+
+    #[test]
+    fn move_subtree_reparents_and_rejects_cycles() {
+        let tree = parse_sgf("(;GM[1](;B[aa];W[bb])(;B[cc]))").unwrap();
+        let mut ed = Editor::new(tree);
+        let root = ed.cursor;
+        let (a, c) = (
+            ed.tree.node(root).children[0],
+            ed.tree.node(root).children[1],
+        );
+        let b = ed.tree.node(a).children[0];
+
+        ed.apply(EditCommand::NavigateToNode(a));
+        ed.apply(EditCommand::MoveSubtree(b));
+        assert_eq!(ed.tree.node(b).parent, Some(a));
+        assert!(ed.undo_stack.is_empty());
+
+        ed.apply(EditCommand::MoveSubtree(c));
+        assert_eq!(ed.tree.node(a).parent, Some(c));
+        assert_eq!(ed.tree.node(root).children, vec![c]);
+        assert_eq!(ed.cursor, a);
+
+        // A failed move after an undo keeps the redo history.
+        ed.apply(EditCommand::Undo);
+        assert_eq!(ed.tree.node(root).children, vec![a, c]);
+        ed.apply(EditCommand::MoveSubtree(b));
+        assert_eq!(ed.redo_stack.len(), 1);
+        ed.apply(EditCommand::Redo);
+        assert_eq!(ed.tree.node(a).parent, Some(c));
+    }
+
+    #[test]
+    fn copy_and_graft_subtree() {
+        let tree = parse_sgf("(;GM[1];B[aa];W[bb])(;GM[1])").unwrap();
+        let mut ed = Editor::new(tree);
+        let second_root = ed.tree.roots[1];
+        ed.apply(EditCommand::NavigateNext);
+        ed.apply(EditCommand::CopySubtree(second_root));
+        assert_eq!(ed.tree.iter_subtree(second_root).count(), 3);
+
+        let other = parse_sgf("(;GM[1];B[cc];W[dd])").unwrap();
+        let node = other.node(other.roots[0]).children[0];
+        ed.apply(EditCommand::NavigateToNode(second_root));
+        ed.apply(EditCommand::GraftSubtree {
+            source: other,
+            node,
+        });
+        assert_eq!(ed.tree.node(second_root).children.len(), 2);
+        assert_eq!(ed.tree.iter_subtree(second_root).count(), 5);
+    }
+
+    #[test]
+    fn navigate_between_transpositions() {
+        let tree = parse_sgf("(;GM[1](;B[dd];W[pp];B[pd])(;B[pd];W[pp];B[dd])(;B[pp]))").unwrap();
+        let mut ed = Editor::new(tree);
+        ed.apply(EditCommand::NavigateLast);
+        let first = ed.cursor;
+        assert_eq!(ed.transpositions().len(), 2);
+
+        ed.apply(EditCommand::NavigateNextTransposition);
+        assert_ne!(ed.cursor, first);
+        assert_eq!(ed.transpositions()[1], ed.cursor);
+        ed.apply(EditCommand::NavigateNextTransposition);
+        assert_eq!(ed.cursor, first);
+        ed.apply(EditCommand::NavigatePrevTransposition);
+        assert_ne!(ed.cursor, first);
+
+        // A node without transpositions stays put.
+        let lone = ed.tree.node(ed.tree.roots[0]).children[2];
+        ed.apply(EditCommand::NavigateToNode(lone));
+        ed.apply(EditCommand::NavigateNextTransposition);
+        assert_eq!(ed.cursor, lone);
+    }
+
+    #[test]
+    fn navigate_to_path() {
+        let tree = parse_sgf("(;GM[1];B[dd](;W[pp])(;W[dp]))").unwrap();
+        let mut ed = Editor::new(tree);
+        ed.apply(EditCommand::NavigateToPath("0:0.1".parse().unwrap()));
+        assert_eq!(ed.tree.node(ed.cursor).properties[0].to_string(), "W[dp]");
+        let before = ed.cursor;
+        ed.apply(EditCommand::NavigateToPath("0:0.5".parse().unwrap()));
+        assert_eq!(ed.cursor, before);
+    }
+
+    #[test]
+    fn play_move_rejects_illegal_moves() {
+        let mut ed = simple_tree();
+        ed.apply(EditCommand::NavigateLast);
+        let leaf = ed.cursor;
+        let dd = GoCoord::new('d', 'd').unwrap();
+        assert_eq!(ed.play_move(Cell::Black, dd), Err(IllegalMove::Occupied));
+        ed.apply(EditCommand::PlayMove {
+            color: Cell::Black,
+            coord: dd,
+        });
+        assert_eq!(ed.cursor, leaf);
+        ed.apply(EditCommand::Undo);
+        assert_eq!(ed.tree.node(leaf).children.len(), 0);
+
+        let pp = GoCoord::new('p', 'p').unwrap();
+        ed.apply(EditCommand::PlayMove {
+            color: Cell::Black,
+            coord: pp,
+        });
+        assert_eq!(ed.tree.node(ed.cursor).parent, Some(leaf));
+        ed.apply(EditCommand::Undo);
+        assert_eq!(ed.cursor, leaf);
+
+        ed.apply(EditCommand::PlayMove {
+            color: Cell::Empty,
+            coord: pp,
+        });
+        assert_eq!(ed.cursor, leaf);
+        assert_eq!(ed.play_move(Cell::Empty, pp), Err(IllegalMove::NoStone));
+    }
 }
 
 /// Interface for editor front-ends.
@@ -388,7 +614,14 @@ pub trait Adapter {
     /// Render the current editor state to the adapter's medium.
     fn render(&mut self, editor: &Editor) -> anyhow::Result<()>;
     /// Produce the next command from user input. `None` signals quit.
-    fn next_command(&mut self) -> anyhow::Result<Option<EditCommand>>;
+    ///
+    /// `editor` is the state last rendered.  Commands that only read it
+    /// (searching, diffing, printing a diagram) can answer from it directly
+    /// instead of returning an [`EditCommand`].  This argument is a breaking
+    /// change from the original `next_command(&mut self)`: it replaces the
+    /// copy of the tree adapters otherwise had to keep from
+    /// [`Adapter::render`].
+    fn next_command(&mut self, editor: &Editor) -> anyhow::Result<Option<EditCommand>>;
 }
 
 /// Run the editor event loop: render → input → apply → repeat.
@@ -399,7 +632,7 @@ pub trait Adapter {
 pub fn run_editor(mut editor: Editor, adapter: &mut impl Adapter) -> anyhow::Result<()> {
     loop {
         adapter.render(&editor)?;
-        match adapter.next_command()? {
+        match adapter.next_command(&editor)? {
             Some(cmd) => editor.apply(cmd),
             None => break,
         }
@@ -49,10 +49,12 @@
 //! |--------|----------|
 //! | [`sgf`] | [`sgf::SGFProperty`], [`GameTree`], [`parse_sgf`], [`write_sgf`], [`sgf::Board`] |
 //! | [`editor`] | [`Editor`], [`EditCommand`], [`Adapter`], [`run_editor`] |
+//! | [`render`] | [`render::TextDiagram`] and [`render::SvgDiagram`] board diagrams |
 
 #[cfg(feature = "cli")]
 pub mod cli;
 pub mod editor;
+pub mod render;
 pub mod sgf;
 
 pub use editor::{Adapter, EditCommand, Editor, run_editor};
@@ -0,0 +1,189 @@
+//! Board diagrams for terminals, documents and images.
+//!
+//! [`TextDiagram`] draws a [`Board`](crate::sgf::Board) as plain text,
+//! Unicode or ANSI-coloured text; [`SvgDiagram`] draws it as a vector image
+//! for documents.  The helpers here are shared by every
+//! renderer: [`Markup`] collects the markup properties of a node,
+//! [`col_label`] names columns the way the GUI does, and [`star_points`]
+//! lists the hoshi for the common board sizes.
+
+pub mod svg;
+pub mod text;
+
+use crate::sgf::{
+    SGFProperty, TreeNode,
+    board::{coord_col, coord_row},
+    node::GoCoord,
+};
+
+pub use svg::{SvgDiagram, SvgStyle};
+pub use text::{TextDiagram, TextStyle};
+
+/// A symbol drawn on a single point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mark {
+    /// `CR`
+    Circle,
+    /// `SQ`
+    Square,
+    /// `TR`
+    Triangle,
+    /// `MA`
+    Cross,
+    /// `LB`, with the label text.
+    Label(String),
+}
+
+/// The markup properties of one node, as `(row, col)` points.
+#[derive(Debug, Clone, Default)]
+pub struct Markup {
+    /// Point symbols and labels, in property order.  Compressed point lists
+    /// are expanded.
+    pub marks: Vec<((usize, usize), Mark)>,
+    /// `AR` arrows as `(from, to)`.
+    pub arrows: Vec<((usize, usize), (usize, usize))>,
+    /// `LN` lines as `(from, to)`.
+    pub lines: Vec<((usize, usize), (usize, usize))>,
+}
+
+impl Markup {
+    /// Collect `CR`, `SQ`, `TR`, `MA`, `LB`, `AR` and `LN` from `props`,
+    /// skipping values that are not valid points.
+    pub fn from_properties(props: &[SGFProperty]) -> Self {
+        let mut markup = Markup::default();
+        for prop in props {
+            let SGFProperty::Unknown(tag, values) = prop else {
+                continue;
+            };
+            for value in values {
+                let mark = match tag.as_str() {
+                    "CR" => Mark::Circle,
+                    "SQ" => Mark::Square,
+                    "TR" => Mark::Triangle,
+                    "MA" => Mark::Cross,
+                    "LB" => {
+                        if let Some((point, text)) = value.split_once(':')
+                            && let Some(point) = parse_point(point)
+                        {
+                            markup.marks.push((point, Mark::Label(text.to_string())));
+                        }
+                        continue;
+                    }
+                    "AR" | "LN" => {
+                        if let Some((from, to)) = value.split_once(':')
+                            && let (Some(from), Some(to)) = (parse_point(from), parse_point(to))
+                        {
+                            let list = if tag == "AR" {
+                                &mut markup.arrows
+                            } else {
+                                &mut markup.lines
+                            };
+                            list.push((from, to));
+                        }
+                        continue;
+                    }
+                    _ => break,
+                };
+                for point in expand_points(value) {
+                    markup.marks.push((point, mark.clone()));
+                }
+            }
+        }
+        markup
+    }
+
+    /// The mark on `(row, col)`, if any.  A later property wins over an
+    /// earlier one on the same point.
+    pub fn mark_at(&self, row: usize, col: usize) -> Option<&Mark> {
+        self.marks
+            .iter()
+            .rev()
+            .find(|(point, _)| *point == (row, col))
+            .map(|(_, mark)| mark)
+    }
+}
+
+/// The `(row, col)` of the move played at `node`, if it has a non-pass `B`
+/// or `W`.
+pub fn last_move(node: &TreeNode) -> Option<(usize, usize)> {
+    node.properties.iter().find_map(|prop| match prop {
+        SGFProperty::B(coord) | SGFProperty::W(coord) if !coord.is_pass() => {
+            Some(coord_point(*coord))
+        }
+        _ => None,
+    })
+}
+
+/// The label for column `col`: `A`–`T`, skipping `I` to avoid confusion
+/// with the digit `1`.
+pub fn col_label(col: usize) -> char {
+    let c = b'A' + col as u8;
+    if c >= b'I' {
+        (c + 1) as char
+    } else {
+        c as char
+    }
+}
+
+/// Star points as `(row, col)` for 19×19, 13×13 and 9×9 boards.
+pub fn star_points(size: usize) -> &'static [(usize, usize)] {
+    match size {
+        19 => &[
+            (3, 3),
+            (3, 9),
+            (3, 15),
+            (9, 3),
+            (9, 9),
+            (9, 15),
+            (15, 3),
+            (15, 9),
+            (15, 15),
+        ],
+        13 => &[(3, 3), (3, 9), (6, 6), (9, 3), (9, 9)],
+        9 => &[(2, 2), (2, 6), (4, 4), (6, 2), (6, 6)],
+        _ => &[],
+    }
+}
+
+fn coord_point(coord: GoCoord) -> (usize, usize) {
+    (coord_row(coord), coord_col(coord))
+}
+
+fn parse_point(value: &str) -> Option<(usize, usize)> {
+    value.parse::<GoCoord>().ok().map(coord_point)
+}
+
+/// A single point or every point of a compressed `point:point` rectangle.
+fn expand_points(value: &str) -> Vec<(usize, usize)> {
+    match value.split_once(':') {
+        None => parse_point(value).into_iter().collect(),
+        Some((a, b)) => {
+            let (Some(a), Some(b)) = (parse_point(a), parse_point(b)) else {
+                return Vec::new();
+            };
+            let rows = a.0.min(b.0)..=a.0.max(b.0);
+            rows.flat_map(|row| (a.1.min(b.1)..=a.1.max(b.1)).map(move |col| (row, col)))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    #[test]
+    fn markup_expands_compressed_points() {
+        let tree = parse_sgf("(;B[dd]TR[aa:bb]LB[cc:A]AR[aa:dd]C[x])").unwrap();
+        let node = tree.node(tree.roots[0]);
+        let markup = Markup::from_properties(&node.properties);
+        assert_eq!(markup.marks.len(), 5);
+        assert_eq!(markup.mark_at(1, 0), Some(&Mark::Triangle));
+        assert_eq!(markup.mark_at(2, 2), Some(&Mark::Label("A".to_string())));
+        assert_eq!(markup.arrows, vec![((0, 0), (3, 3))]);
+        assert_eq!(last_move(node), Some((3, 3)));
+        assert_eq!(col_label(7), 'H');
+        assert_eq!(col_label(8), 'J');
+    }
+}
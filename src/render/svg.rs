@@ -0,0 +1,623 @@
+use std::{
+    fmt::{Display, Formatter, Write as _},
+    ops::RangeBounds,
+};
+
+use crate::{
+    render::{Mark, Markup, col_label, last_move, star_points},
+    sgf::{Board, Cell, GameTree, NodeId, TreeNode},
+};
+
+/// Colours and proportions for an [`SvgDiagram`].  Colours are any SVG
+/// paint value.
+#[derive(Debug, Clone)]
+pub struct SvgStyle {
+    /// Distance between adjacent lines, in SVG user units.
+    pub cell_size: f64,
+    pub board_color: String,
+    pub line_color: String,
+    pub black_color: String,
+    pub white_color: String,
+    /// Colour of markup on empty points and of coordinates.
+    pub markup_color: String,
+    pub font_family: String,
+    /// Draw column letters above and row numbers left of the board.
+    pub coordinates: bool,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            cell_size: 24.0,
+            board_color: "#dcb35c".to_string(),
+            line_color: "#000000".to_string(),
+            black_color: "#000000".to_string(),
+            white_color: "#ffffff".to_string(),
+            markup_color: "#000000".to_string(),
+            font_family: "sans-serif".to_string(),
+            coordinates: true,
+        }
+    }
+}
+
+/// An SVG drawing of a [`Board`], built with chained setters and written
+/// through [`Display`].
+///
+/// The output depends only on the inputs, with coordinates rounded to two
+/// decimals, so it can be compared against stored snapshots.
+///
+/// ```
+/// use tesuji::parse_sgf;
+/// use tesuji::render::{SvgDiagram, SvgStyle};
+/// use tesuji::sgf::Board;
+///
+/// let tree = parse_sgf("(;SZ[19];B[pd];W[dp])").unwrap();
+/// let root = tree.roots[0];
+/// let last = tree.iter_mainline(root).last().unwrap().0;
+/// let board = Board::from_tree(&tree, last);
+/// let style = SvgStyle {
+///     coordinates: false,
+///     ..SvgStyle::default()
+/// };
+/// let svg = SvgDiagram::new(&board)
+///     .style(style)
+///     .sequence(&tree, root, last)
+///     .to_string();
+/// assert!(svg.starts_with("<svg"));
+/// assert!(svg.contains(">2</text>"));
+/// ```
+pub struct SvgDiagram<'a> {
+    board: &'a Board,
+    style: SvgStyle,
+    markup: Markup,
+    last_move: Option<(usize, usize)>,
+    numbers: Vec<((usize, usize), usize)>,
+    /// Inclusive `(top, left, bottom, right)` rows and columns to draw.
+    crop: (usize, usize, usize, usize),
+}
+
+impl<'a> SvgDiagram<'a> {
+    /// A diagram of the whole of `board` in the default style.
+    pub fn new(board: &'a Board) -> Self {
+        let last = board.size - 1;
+        Self {
+            board,
+            style: SvgStyle::default(),
+            markup: Markup::default(),
+            last_move: None,
+            numbers: Vec::new(),
+            crop: (0, 0, last, last),
+        }
+    }
+
+    /// Draw in `style`.  Its colours and font family are escaped here, once,
+    /// for use in attribute values.
+    pub fn style(mut self, style: SvgStyle) -> Self {
+        self.style = SvgStyle {
+            board_color: escape(&style.board_color),
+            line_color: escape(&style.line_color),
+            black_color: escape(&style.black_color),
+            white_color: escape(&style.white_color),
+            markup_color: escape(&style.markup_color),
+            font_family: escape(&style.font_family),
+            ..style
+        };
+        self
+    }
+
+    pub fn markup(mut self, markup: Markup) -> Self {
+        self.markup = markup;
+        self
+    }
+
+    /// Mark the stone at `(row, col)` as the last move, unless it carries a
+    /// move number.
+    pub fn last_move(mut self, point: Option<(usize, usize)>) -> Self {
+        self.last_move = point;
+        self
+    }
+
+    /// Take the last move and markup from `node`, usually the node the
+    /// board was built at.
+    pub fn node(self, node: &TreeNode) -> Self {
+        self.last_move(last_move(node))
+            .markup(Markup::from_properties(&node.properties))
+    }
+
+    /// Number stones with the given move numbers.  Numbers on points
+    /// without a stone are not drawn.
+    pub fn move_numbers(
+        mut self,
+        numbers: impl IntoIterator<Item = ((usize, usize), usize)>,
+    ) -> Self {
+        self.numbers.extend(numbers);
+        self
+    }
+
+    /// Number the stones on the board whose move number, as given by
+    /// [`Board::move_number_at`], falls in `range`.
+    pub fn number_moves(self, range: impl RangeBounds<usize>) -> Self {
+        let numbers: Vec<_> = self
+            .board
+            .numbered_stones()
+            .filter(|(_, number)| range.contains(number))
+            .collect();
+        self.move_numbers(numbers)
+    }
+
+    /// Number the moves played after `from` down to `to`, as in a figure
+    /// of a game.  Numbers follow the game's move count, including `MN`
+    /// renumbering.  When a point is played more than once, the stone shows
+    /// its latest number.  `from` must be `to` or one of its ancestors.
+    pub fn sequence(self, tree: &GameTree, from: NodeId, to: NodeId) -> Self {
+        debug_assert!(
+            tree.iter_ancestors(to).any(|(id, _)| id == from),
+            "`from` is not an ancestor of `to`"
+        );
+        let mut path: Vec<NodeId> = tree
+            .iter_ancestors(to)
+            .map(|(id, _)| id)
+            .take_while(|&id| id != from)
+            .collect();
+        path.reverse();
+        let mut board = Board::from_tree(tree, from);
+        let mut numbers = Vec::new();
+        for id in path {
+            let node = tree.node(id);
+            board.apply_node(node);
+            if let Some((row, col)) = last_move(node)
+                && let Some(number) = board.move_number_at(row, col)
+            {
+                numbers.retain(|&(p, _)| p != (row, col));
+                numbers.push(((row, col), number));
+            }
+        }
+        self.move_numbers(numbers)
+    }
+
+    /// Draw only rows `top..=bottom` and columns `left..=right`.  Lines are
+    /// continued half a cell past crop edges that are not board edges.
+    pub fn crop(mut self, top: usize, left: usize, bottom: usize, right: usize) -> Self {
+        let last = self.board.size - 1;
+        let (top, left) = (top.min(last), left.min(last));
+        self.crop = (top, left, bottom.clamp(top, last), right.clamp(left, last));
+        self
+    }
+
+    fn padding(&self) -> f64 {
+        if self.style.coordinates {
+            self.style.cell_size
+        } else {
+            0.0
+        }
+    }
+
+    fn x(&self, col: usize) -> f64 {
+        self.padding()
+            + (col - self.crop.1) as f64 * self.style.cell_size
+            + self.style.cell_size / 2.0
+    }
+
+    fn y(&self, row: usize) -> f64 {
+        self.padding()
+            + (row - self.crop.0) as f64 * self.style.cell_size
+            + self.style.cell_size / 2.0
+    }
+
+    fn in_crop(&self, (row, col): (usize, usize)) -> bool {
+        let (top, left, bottom, right) = self.crop;
+        (top..=bottom).contains(&row) && (left..=right).contains(&col)
+    }
+
+    /// The colour that contrasts with whatever is on `point`.
+    fn ink(&self, point: (usize, usize)) -> &str {
+        match self.board.cells[point.0][point.1] {
+            Cell::Black => &self.style.white_color,
+            Cell::White => &self.style.black_color,
+            Cell::Empty => &self.style.markup_color,
+        }
+    }
+
+    fn write_grid(&self, out: &mut String) -> std::fmt::Result {
+        let (top, left, bottom, right) = self.crop;
+        let last = self.board.size - 1;
+        let half = self.style.cell_size / 2.0;
+        let x0 = self.x(left) - if left > 0 { half } else { 0.0 };
+        let x1 = self.x(right) + if right < last { half } else { 0.0 };
+        let y0 = self.y(top) - if top > 0 { half } else { 0.0 };
+        let y1 = self.y(bottom) + if bottom < last { half } else { 0.0 };
+        let mut d = String::new();
+        for row in top..=bottom {
+            write!(d, "M{} {}H{}", num(x0), num(self.y(row)), num(x1))?;
+        }
+        for col in left..=right {
+            write!(d, "M{} {}V{}", num(self.x(col)), num(y0), num(y1))?;
+        }
+        writeln!(
+            out,
+            r#"<path d="{d}" stroke="{}" stroke-width="1" fill="none"/>"#,
+            self.style.line_color
+        )?;
+        for &point in star_points(self.board.size) {
+            if self.in_crop(point) {
+                writeln!(
+                    out,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                    num(self.x(point.1)),
+                    num(self.y(point.0)),
+                    num(self.style.cell_size * 0.1),
+                    self.style.line_color
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_coordinates(&self, out: &mut String) -> std::fmt::Result {
+        let (top, left, bottom, right) = self.crop;
+        let half = self.style.cell_size / 2.0;
+        let font = self.font_size();
+        for col in left..=right {
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" {}>{}</text>"#,
+                num(self.x(col)),
+                num(half),
+                self.text_attrs(font, &self.style.markup_color),
+                col_label(col)
+            )?;
+        }
+        for row in top..=bottom {
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" {}>{}</text>"#,
+                num(half),
+                num(self.y(row)),
+                self.text_attrs(font, &self.style.markup_color),
+                self.board.size - row
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_stones(&self, out: &mut String) -> std::fmt::Result {
+        let (top, left, bottom, right) = self.crop;
+        for row in top..=bottom {
+            for col in left..=right {
+                let (fill, stroke) = match self.board.cells[row][col] {
+                    Cell::Empty => continue,
+                    Cell::Black => (&self.style.black_color, &self.style.black_color),
+                    Cell::White => (&self.style.white_color, &self.style.line_color),
+                };
+                writeln!(
+                    out,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="{fill}" stroke="{stroke}" stroke-width="1"/>"#,
+                    num(self.x(col)),
+                    num(self.y(row)),
+                    num(self.style.cell_size * 0.48),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_numbers(&self, out: &mut String) -> std::fmt::Result {
+        let font = self.font_size();
+        for &(point, number) in &self.numbers {
+            if !self.in_crop(point) || self.board.cells[point.0][point.1] == Cell::Empty {
+                continue;
+            }
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" {}>{number}</text>"#,
+                num(self.x(point.1)),
+                num(self.y(point.0)),
+                self.text_attrs(font, self.ink(point)),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn write_markup(&self, out: &mut String) -> std::fmt::Result {
+        let cell = self.style.cell_size;
+        for (point, mark) in &self.markup.marks {
+            if !self.in_crop(*point) {
+                continue;
+            }
+            let (x, y) = (self.x(point.1), self.y(point.0));
+            let ink = self.ink(*point);
+            let r = cell * 0.25;
+            match mark {
+                Mark::Circle => writeln!(
+                    out,
+                    r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{ink}" stroke-width="2"/>"#,
+                    num(x),
+                    num(y),
+                    num(r)
+                )?,
+                Mark::Square => writeln!(
+                    out,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{ink}" stroke-width="2"/>"#,
+                    num(x - r),
+                    num(y - r),
+                    num(2.0 * r),
+                    num(2.0 * r)
+                )?,
+                Mark::Triangle => writeln!(
+                    out,
+                    r#"<polygon points="{},{} {},{} {},{}" fill="none" stroke="{ink}" stroke-width="2"/>"#,
+                    num(x),
+                    num(y - r * 1.2),
+                    num(x - r * 1.1),
+                    num(y + r * 0.7),
+                    num(x + r * 1.1),
+                    num(y + r * 0.7)
+                )?,
+                Mark::Cross => writeln!(
+                    out,
+                    r#"<path d="M{} {}L{} {}M{} {}L{} {}" stroke="{ink}" stroke-width="2"/>"#,
+                    num(x - r),
+                    num(y - r),
+                    num(x + r),
+                    num(y + r),
+                    num(x - r),
+                    num(y + r),
+                    num(x + r),
+                    num(y - r)
+                )?,
+                Mark::Label(text) => {
+                    if self.board.cells[point.0][point.1] == Cell::Empty {
+                        // Clear the grid lines behind the label.
+                        writeln!(
+                            out,
+                            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                            num(x - r * 1.4),
+                            num(y - r * 1.4),
+                            num(r * 2.8),
+                            num(r * 2.8),
+                            self.style.board_color
+                        )?;
+                    }
+                    writeln!(
+                        out,
+                        r#"<text x="{}" y="{}" {}>{}</text>"#,
+                        num(x),
+                        num(y),
+                        self.text_attrs(self.font_size(), ink),
+                        escape(text)
+                    )?;
+                }
+            }
+        }
+
+        for (lines, marker) in [
+            (&self.markup.lines, ""),
+            (&self.markup.arrows, r#" marker-end="url(#arrow)""#),
+        ] {
+            for &(from, to) in lines {
+                if !self.in_crop(from) || !self.in_crop(to) {
+                    continue;
+                }
+                writeln!(
+                    out,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="2"{marker}/>"#,
+                    num(self.x(from.1)),
+                    num(self.y(from.0)),
+                    num(self.x(to.1)),
+                    num(self.y(to.0)),
+                    self.style.markup_color
+                )?;
+            }
+        }
+
+        if let Some(point) = self.last_move
+            && self.in_crop(point)
+            && self.board.cells[point.0][point.1] != Cell::Empty
+            && !self.numbers.iter().any(|&(p, _)| p == point)
+        {
+            writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+                num(self.x(point.1)),
+                num(self.y(point.0)),
+                num(cell * 0.22),
+                self.ink(point)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn font_size(&self) -> f64 {
+        self.style.cell_size * 0.5
+    }
+
+    fn text_attrs(&self, size: f64, fill: &str) -> String {
+        format!(
+            r#"font-family="{}" font-size="{}" fill="{fill}" text-anchor="middle" dominant-baseline="central""#,
+            self.style.font_family,
+            num(size)
+        )
+    }
+}
+
+impl Display for SvgDiagram<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (top, left, bottom, right) = self.crop;
+        let cell = self.style.cell_size;
+        let width = self.padding() + (right - left + 1) as f64 * cell;
+        let height = self.padding() + (bottom - top + 1) as f64 * cell;
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = num(width),
+            h = num(height)
+        )?;
+        if !self.markup.arrows.is_empty() {
+            writeln!(
+                out,
+                r#"<defs><marker id="arrow" viewBox="0 0 10 10" refX="9" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M0 0L10 5L0 10z" fill="{}"/></marker></defs>"#,
+                self.style.markup_color
+            )?;
+        }
+        writeln!(
+            out,
+            r#"<rect width="{}" height="{}" fill="{}"/>"#,
+            num(width),
+            num(height),
+            self.style.board_color
+        )?;
+        if self.style.coordinates {
+            self.write_coordinates(&mut out)?;
+        }
+        self.write_grid(&mut out)?;
+        self.write_stones(&mut out)?;
+        self.write_numbers(&mut out)?;
+        self.write_markup(&mut out)?;
+        writeln!(out, "</svg>")?;
+        f.write_str(&out)
+    }
+}
+
+/// Format a length with at most two decimals and no trailing zeros.
+fn num(value: f64) -> String {
+    let s = format!("{:.2}", value);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    #[test]
+    fn cropped_snapshot() {
+        let tree = parse_sgf("(;AB[aa]AW[ba]LB[ab:<1>]TR[aa])").unwrap();
+        let root = tree.roots[0];
+        let board = Board::from_tree(&tree, root);
+        let svg = SvgDiagram::new(&board)
+            .style(SvgStyle {
+                cell_size: 20.0,
+                ..SvgStyle::default()
+            })
+            .node(tree.node(root))
+            .crop(0, 0, 1, 1)
+            .to_string();
+        let expected = r##"<svg xmlns="http://www.w3.org/2000/svg" width="60" height="60" viewBox="0 0 60 60">
+<rect width="60" height="60" fill="#dcb35c"/>
+<text x="30" y="10" font-family="sans-serif" font-size="10" fill="#000000" text-anchor="middle" dominant-baseline="central">A</text>
+<text x="50" y="10" font-family="sans-serif" font-size="10" fill="#000000" text-anchor="middle" dominant-baseline="central">B</text>
+<text x="10" y="30" font-family="sans-serif" font-size="10" fill="#000000" text-anchor="middle" dominant-baseline="central">19</text>
+<text x="10" y="50" font-family="sans-serif" font-size="10" fill="#000000" text-anchor="middle" dominant-baseline="central">18</text>
+<path d="M30 30H60M30 50H60M30 30V60M50 30V60" stroke="#000000" stroke-width="1" fill="none"/>
+<circle cx="30" cy="30" r="9.6" fill="#000000" stroke="#000000" stroke-width="1"/>
+<circle cx="50" cy="30" r="9.6" fill="#ffffff" stroke="#000000" stroke-width="1"/>
+<rect x="23" y="43" width="14" height="14" fill="#dcb35c"/>
+<text x="30" y="50" font-family="sans-serif" font-size="10" fill="#000000" text-anchor="middle" dominant-baseline="central">&lt;1&gt;</text>
+<polygon points="30,24 24.5,33.5 35.5,33.5" fill="none" stroke="#ffffff" stroke-width="2"/>
+</svg>
+"##;
+        assert_eq!(svg, expected);
+    }
+
+    #[test]
+    fn crop_is_clamped_to_the_board() {
+        let board = Board::new();
+        let diagram = SvgDiagram::new(&board).crop(20, 0, 25, 5);
+        assert_eq!(diagram.crop, (18, 0, 18, 5));
+        let diagram = SvgDiagram::new(&board).crop(3, 30, 1, 40);
+        assert_eq!(diagram.crop, (3, 18, 3, 18));
+
+        let tree = parse_sgf("(;SZ[9]AB[cc])").unwrap();
+        let small = Board::from_tree(&tree, tree.roots[0]);
+        let diagram = SvgDiagram::new(&small);
+        assert_eq!(diagram.crop, (0, 0, 8, 8));
+        let svg = diagram.to_string();
+        assert!(svg.contains(">9</text>") && svg.contains(">J</text>"));
+        assert!(!svg.contains(">10</text>") && !svg.contains(">K</text>"));
+        assert_eq!(svg.matches(r#"r="2.4""#).count(), 5);
+        assert_eq!(
+            SvgDiagram::new(&small).crop(0, 0, 20, 20).crop,
+            (0, 0, 8, 8)
+        );
+    }
+
+    #[test]
+    fn style_strings_are_escaped() {
+        let tree = parse_sgf("(;AB[aa]LB[bb:x])").unwrap();
+        let board = Board::from_tree(&tree, tree.roots[0]);
+        let style = SvgStyle {
+            board_color: r#""/><script>"#.to_string(),
+            black_color: "a&b".to_string(),
+            font_family: "\"Go\" font".to_string(),
+            ..SvgStyle::default()
+        };
+        let svg = SvgDiagram::new(&board)
+            .style(style)
+            .node(tree.node(tree.roots[0]))
+            .to_string();
+        assert!(!svg.contains("<script>"));
+        assert!(svg.contains(r#"fill="&quot;/&gt;&lt;script&gt;""#));
+        assert!(svg.contains(r#"fill="a&amp;b""#));
+        assert!(svg.contains(r#"font-family="&quot;Go&quot; font""#));
+    }
+
+    #[test]
+    fn sequence_numbers_and_arrows() {
+        let tree = parse_sgf("(;B[aa];W[ba];B[ca]AR[aa:ca])").unwrap();
+        let root = tree.roots[0];
+        let last = tree.iter_mainline(root).last().unwrap().0;
+        let board = Board::from_tree(&tree, last);
+        let render = || {
+            SvgDiagram::new(&board)
+                .style(SvgStyle {
+                    coordinates: false,
+                    ..SvgStyle::default()
+                })
+                .node(tree.node(last))
+                .sequence(&tree, root, last)
+                .to_string()
+        };
+        let svg = render();
+        assert!(!svg.contains(">1</text>"));
+        assert!(svg.contains(">2</text>"));
+        assert!(svg.contains(">3</text>"));
+        assert!(svg.contains(r#"marker-end="url(#arrow)""#));
+        // The numbered last move gets no extra marker.
+        assert_eq!(svg.matches(r#"fill="none" stroke"#).count(), 0);
+        assert_eq!(svg, render());
+    }
+
+    #[test]
+    fn numbers_follow_mn() {
+        let tree = parse_sgf("(;B[aa];W[ba]MN[40];B[ca])").unwrap();
+        let root = tree.roots[0];
+        let last = tree.iter_mainline(root).last().unwrap().0;
+        let board = Board::from_tree(&tree, last);
+        let style = SvgStyle {
+            coordinates: false,
+            ..SvgStyle::default()
+        };
+        let svg = SvgDiagram::new(&board)
+            .style(style.clone())
+            .sequence(&tree, root, last)
+            .to_string();
+        assert!(!svg.contains(">1</text>"));
+        assert!(svg.contains(">40</text>"));
+        assert!(svg.contains(">41</text>"));
+        let svg = SvgDiagram::new(&board)
+            .style(style)
+            .number_moves(41..)
+            .to_string();
+        assert!(!svg.contains(">40</text>"));
+        assert!(svg.contains(">41</text>"));
+    }
+}
@@ -0,0 +1,347 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    render::{Mark, Markup, col_label, last_move, star_points},
+    sgf::{Board, Cell, TreeNode},
+};
+
+const RESET: &str = "\x1b[0m";
+const BOARD_BG: &str = "\x1b[43m";
+const GRID_FG: &str = "\x1b[90m";
+const BLACK_FG: &str = "\x1b[30m";
+const WHITE_FG: &str = "\x1b[97m";
+const MARK_FG: &str = "\x1b[34m";
+const LAST_MOVE_FG: &str = "\x1b[31m";
+
+/// Character set used by a [`TextDiagram`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextStyle {
+    /// Plain ASCII in the Sensei's Library convention: `X`/`O` stones, `.`
+    /// points and `,` star points.  Marked stones are `B`/`W` (circle),
+    /// `#`/`@` (square), `Y`/`Q` (triangle) and `Z`/`P` (cross); marked empty
+    /// points are `C`, `S`, `T` and `M`.
+    #[default]
+    Ascii,
+    /// `●`/`○` stones on box-drawing grid lines.
+    Unicode,
+    /// [`TextStyle::Unicode`] with ANSI colours: a yellow board, coloured
+    /// stones, blue markup and a red last-move marker.
+    Ansi,
+}
+
+/// A text drawing of a [`Board`], built with chained setters and printed
+/// through [`Display`].
+///
+/// The last move is bracketed as `(X)`, the ko point is marked `K` (`◇`
+/// in Unicode) and markup from the node is drawn over the points.  With
+/// [`TextDiagram::number_moves`] a run of ten moves is shown as digits.
+///
+/// ```
+/// use tesuji::parse_sgf;
+/// use tesuji::render::{TextDiagram, TextStyle};
+/// use tesuji::sgf::Board;
+///
+/// let tree = parse_sgf("(;SZ[19];B[pd]TR[dd])").unwrap();
+/// let node = tree.iter_mainline(tree.roots[0]).last().unwrap().0;
+/// let board = Board::from_tree(&tree, node);
+/// let diagram = TextDiagram::new(&board)
+///     .style(TextStyle::Ascii)
+///     .node(tree.node(node))
+///     .to_string();
+/// assert!(diagram.contains(". T ."));
+/// assert!(diagram.contains("(X)"));
+/// ```
+pub struct TextDiagram<'a> {
+    board: &'a Board,
+    style: TextStyle,
+    coordinates: bool,
+    last_move: Option<(usize, usize)>,
+    show_ko: bool,
+    markup: Markup,
+    first_numbered: Option<usize>,
+}
+
+impl<'a> TextDiagram<'a> {
+    /// An ASCII diagram of `board` with coordinates and the ko marker.
+    pub fn new(board: &'a Board) -> Self {
+        Self {
+            board,
+            style: TextStyle::default(),
+            coordinates: true,
+            last_move: None,
+            show_ko: true,
+            markup: Markup::default(),
+            first_numbered: None,
+        }
+    }
+
+    pub fn style(mut self, style: TextStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Print column letters and row numbers around the board.
+    pub fn coordinates(mut self, show: bool) -> Self {
+        self.coordinates = show;
+        self
+    }
+
+    /// Bracket the stone at `(row, col)` as the last move.
+    pub fn last_move(mut self, point: Option<(usize, usize)>) -> Self {
+        self.last_move = point;
+        self
+    }
+
+    /// Mark [`Board::ko_point`], if any.
+    pub fn ko(mut self, show: bool) -> Self {
+        self.show_ko = show;
+        self
+    }
+
+    pub fn markup(mut self, markup: Markup) -> Self {
+        self.markup = markup;
+        self
+    }
+
+    /// Show the stones played as moves `first` to `first + 9` (see
+    /// [`Board::move_number_at`]) as the digits `1`–`9` and `0`, the
+    /// Sensei's Library convention for a diagram of a ten-move sequence.
+    /// Markup still wins over a number.
+    pub fn number_moves(mut self, first: usize) -> Self {
+        self.first_numbered = Some(first);
+        self
+    }
+
+    /// Take the last move and markup from `node`, usually the node the
+    /// board was built at.
+    pub fn node(self, node: &TreeNode) -> Self {
+        self.last_move(last_move(node))
+            .markup(Markup::from_properties(&node.properties))
+    }
+
+    fn ansi(&self) -> bool {
+        self.style == TextStyle::Ansi
+    }
+
+    fn glyph(&self, row: usize, col: usize) -> (char, &'static str) {
+        let ascii = self.style == TextStyle::Ascii;
+        let mark = self.markup.mark_at(row, col);
+        match self.board.cells[row][col] {
+            Cell::Empty => {
+                let c =
+                    match mark {
+                        Some(Mark::Label(text)) => text.chars().next().unwrap_or(' '),
+                        Some(mark) => marked(mark, Cell::Empty, ascii),
+                        None if self.show_ko && self.board.ko_point == Some((row, col)) => {
+                            if ascii { 'K' } else { '◇' }
+                        }
+                        None => return (self.grid(row, col), GRID_FG),
+                    };
+                (c, MARK_FG)
+            }
+            color => {
+                let fg = if color == Cell::Black {
+                    BLACK_FG
+                } else {
+                    WHITE_FG
+                };
+                let c = match mark {
+                    Some(Mark::Label(text)) => text.chars().next().unwrap_or(' '),
+                    Some(mark) => marked(mark, color, ascii),
+                    None if let Some(digit) = self.move_digit(row, col) => digit,
+                    None => match (color, ascii) {
+                        (Cell::Black, true) => 'X',
+                        (_, true) => 'O',
+                        (Cell::Black, false) => '●',
+                        (_, false) => '○',
+                    },
+                };
+                (c, fg)
+            }
+        }
+    }
+
+    /// The digit for the stone on `(row, col)` under
+    /// [`TextDiagram::number_moves`].
+    fn move_digit(&self, row: usize, col: usize) -> Option<char> {
+        let first = self.first_numbered?;
+        let offset = self.board.move_number_at(row, col)?.checked_sub(first)?;
+        (offset < 10).then(|| char::from(b'0' + ((offset + 1) % 10) as u8))
+    }
+
+    fn grid(&self, row: usize, col: usize) -> char {
+        let star = star_points(self.board.size).contains(&(row, col));
+        if self.style == TextStyle::Ascii {
+            return if star { ',' } else { '.' };
+        }
+        let last = self.board.size - 1;
+        match (row, col) {
+            _ if star => '╋',
+            (0, 0) => '┌',
+            (0, c) if c == last => '┐',
+            (r, 0) if r == last => '└',
+            (r, c) if r == last && c == last => '┘',
+            (0, _) => '┬',
+            (r, _) if r == last => '┴',
+            (_, 0) => '├',
+            (_, c) if c == last => '┤',
+            _ => '┼',
+        }
+    }
+
+    /// The character between columns `col - 1` and `col` (or the board
+    /// edge), carrying the brackets around the last move.
+    fn separator(&self, row: usize, col: usize) -> (char, &'static str) {
+        match self.last_move {
+            Some((r, c)) if r == row && c == col => ('(', LAST_MOVE_FG),
+            Some((r, c)) if r == row && c + 1 == col => (')', LAST_MOVE_FG),
+            _ if col == 0 || col == self.board.size || self.style == TextStyle::Ascii => {
+                (' ', GRID_FG)
+            }
+            _ => ('─', GRID_FG),
+        }
+    }
+
+    fn write_columns(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "   ")?;
+        for col in 0..self.board.size {
+            write!(f, " {}", col_label(col))?;
+        }
+        writeln!(f)
+    }
+}
+
+impl Display for TextDiagram<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let size = self.board.size;
+        if self.coordinates {
+            self.write_columns(f)?;
+        }
+        for row in 0..size {
+            if self.coordinates {
+                write!(f, "{:>2} ", size - row)?;
+            }
+            if self.ansi() {
+                write!(f, "{BOARD_BG}")?;
+            }
+            for col in 0..=size {
+                let (sep, sep_fg) = self.separator(row, col);
+                if self.ansi() {
+                    write!(f, "{sep_fg}")?;
+                }
+                write!(f, "{sep}")?;
+                if col < size {
+                    let (glyph, fg) = self.glyph(row, col);
+                    if self.ansi() {
+                        write!(f, "{fg}")?;
+                    }
+                    write!(f, "{glyph}")?;
+                }
+            }
+            if self.ansi() {
+                write!(f, "{RESET}")?;
+            }
+            if self.coordinates {
+                write!(f, "{}", size - row)?;
+            }
+            writeln!(f)?;
+        }
+        if self.coordinates {
+            self.write_columns(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn marked(mark: &Mark, color: Cell, ascii: bool) -> char {
+    let glyphs = match (color, ascii) {
+        (Cell::Black, true) => ['B', '#', 'Y', 'Z'],
+        (Cell::White, true) => ['W', '@', 'Q', 'P'],
+        (Cell::Empty, true) => ['C', 'S', 'T', 'M'],
+        (Cell::Black, false) => ['◉', '■', '▲', '✖'],
+        (Cell::White, false) => ['◎', '□', '△', '✕'],
+        (Cell::Empty, false) => ['◦', '▫', '▵', '×'],
+    };
+    match mark {
+        Mark::Circle => glyphs[0],
+        Mark::Square => glyphs[1],
+        Mark::Triangle => glyphs[2],
+        Mark::Cross | Mark::Label(_) => glyphs[3],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.roots[0])
+    }
+
+    #[test]
+    fn ascii_diagram_with_coordinates() {
+        let b = board("(;AB[aa][dd]AW[ba]SQ[ba]CR[ca])");
+        let tree = parse_sgf("(;AB[aa][dd]AW[ba]SQ[ba]CR[ca])").unwrap();
+        let text = TextDiagram::new(&b)
+            .node(tree.node(tree.roots[0]))
+            .last_move(Some((0, 0)))
+            .to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 21);
+        assert_eq!(lines[0], "    A B C D E F G H J K L M N O P Q R S T");
+        assert!(lines[1].starts_with("19 (X)@ C . ."), "{}", lines[1]);
+        assert!(lines[1].ends_with(". 19"));
+        assert!(lines[4].starts_with("16  . . . X . ."), "{}", lines[4]);
+        assert!(lines[10].contains(". , ."));
+        assert_eq!(lines[19], lines[19].trim_end());
+    }
+
+    #[test]
+    fn small_boards_render_at_their_size() {
+        let b = board("(;SZ[9]AB[cc]AW[gg])");
+        let text = TextDiagram::new(&b).to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[0], "    A B C D E F G H J");
+        assert_eq!(lines[1], " 9  . . . . . . . . . 9");
+        assert_eq!(lines[3], " 7  . . X . . . , . . 7");
+        assert_eq!(lines[5], " 5  . . . . , . . . . 5");
+        assert_eq!(lines[7], " 3  . . , . . . O . . 3");
+    }
+
+    #[test]
+    fn numbers_a_run_of_moves() {
+        let tree = parse_sgf("(;B[aa];W[ba];B[ca]MN[57];W[da];B[ea])").unwrap();
+        let last = tree.iter_mainline(tree.roots[0]).last().unwrap().0;
+        let b = Board::from_tree(&tree, last);
+        assert_eq!(b.move_number, 59);
+        let text = TextDiagram::new(&b)
+            .coordinates(false)
+            .number_moves(57)
+            .to_string();
+        assert!(text.starts_with(" X O 1 2 3 ."), "{text}");
+    }
+
+    #[test]
+    fn unicode_and_ansi_styles() {
+        let mut b = board("(;AB[ab]AW[bb])");
+        b.ko_point = Some((2, 1));
+        let text = TextDiagram::new(&b)
+            .style(TextStyle::Unicode)
+            .coordinates(false)
+            .to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 19);
+        assert!(lines[0].starts_with(" ┌─┬─┬"));
+        assert!(lines[1].starts_with(" ●─○─┼"));
+        assert!(lines[2].starts_with(" ├─◇─┼"));
+        assert!(lines[18].ends_with("┴─┘ "));
+
+        let ansi = TextDiagram::new(&b).style(TextStyle::Ansi).to_string();
+        assert!(ansi.contains(BOARD_BG));
+        assert!(ansi.contains(&format!("{BLACK_FG}●")));
+        assert_eq!(ansi.matches(RESET).count(), 19);
+    }
+}
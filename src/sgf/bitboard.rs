@@ -0,0 +1,569 @@
+use std::ops::{BitAnd, BitOr, Sub};
+
+use crate::sgf::{
+    Board, Cell, Chain, ChainMap, GameTree, IllegalMove, MoveOutcome, NodeId, Rules, TreeNode,
+    board::{Replay, Settings, coord_col, coord_row, replay_node},
+    node::GoCoord,
+    zobrist,
+};
+
+/// A set of board points stored as one `u32` of column bits per row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bits {
+    rows: [u32; 19],
+}
+
+impl Bits {
+    /// The empty set.
+    pub const EMPTY: Bits = Bits { rows: [0; 19] };
+
+    /// A set holding only `(row, col)`.
+    pub fn single(row: usize, col: usize) -> Self {
+        let mut bits = Self::EMPTY;
+        bits.insert(row, col);
+        bits
+    }
+
+    /// Every point of a `size`×`size` board.
+    pub fn full(size: usize) -> Self {
+        let mut bits = Self::EMPTY;
+        for row in bits.rows.iter_mut().take(size) {
+            *row = (1 << size) - 1;
+        }
+        bits
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.rows[row] & (1 << col) != 0
+    }
+
+    pub fn insert(&mut self, row: usize, col: usize) {
+        self.rows[row] |= 1 << col;
+    }
+
+    pub fn remove(&mut self, row: usize, col: usize) {
+        self.rows[row] &= !(1 << col);
+    }
+
+    /// Number of points in the set.
+    pub fn len(&self) -> usize {
+        self.rows.iter().map(|r| r.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&r| r == 0)
+    }
+
+    /// The first point in row-major order.
+    pub fn first(&self) -> Option<(usize, usize)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .find(|(_, r)| **r != 0)
+            .map(|(row, r)| (row, r.trailing_zeros() as usize))
+    }
+
+    /// The points in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.rows.iter().enumerate().flat_map(|(row, &bits)| {
+            (0..19)
+                .filter(move |col| bits & (1 << col) != 0)
+                .map(move |col| (row, col))
+        })
+    }
+
+    /// The set plus every orthogonal neighbour of its points on a
+    /// `size`×`size` board.
+    pub fn dilate(&self, size: usize) -> Self {
+        let mask = (1u32 << size) - 1;
+        let mut out = Self::EMPTY;
+        for row in 0..size {
+            let x = self.rows[row];
+            out.rows[row] |= (x | (x << 1) | (x >> 1)) & mask;
+            if row > 0 {
+                out.rows[row - 1] |= x;
+            }
+            if row + 1 < size {
+                out.rows[row + 1] |= x;
+            }
+        }
+        out
+    }
+}
+
+impl BitAnd for Bits {
+    type Output = Bits;
+
+    fn bitand(mut self, rhs: Bits) -> Bits {
+        for (a, b) in self.rows.iter_mut().zip(rhs.rows) {
+            *a &= b;
+        }
+        self
+    }
+}
+
+impl BitOr for Bits {
+    type Output = Bits;
+
+    fn bitor(mut self, rhs: Bits) -> Bits {
+        for (a, b) in self.rows.iter_mut().zip(rhs.rows) {
+            *a |= b;
+        }
+        self
+    }
+}
+
+impl Sub for Bits {
+    type Output = Bits;
+
+    /// Set difference.
+    fn sub(mut self, rhs: Bits) -> Bits {
+        for (a, b) in self.rows.iter_mut().zip(rhs.rows) {
+            *a &= !b;
+        }
+        self
+    }
+}
+
+/// A bitboard-backed alternative to [`Board`] for bulk replay and analysis.
+///
+/// It replays nodes through the same routine as [`Board::apply_node`]
+/// (captures, simple ko, suicide per [`Rules`], `SZ`, `KM`, `RU`, `PL` and
+/// `MN`) and offers the same moves, legality checks and group, liberty and
+/// chain queries, but stores each colour as a [`Bits`] set so groups and
+/// liberties are found with word-wide operations instead of allocating
+/// flood fills.  It keeps no superko history, so [`BitBoard::play`] and
+/// [`BitBoard::is_legal`] never report [`IllegalMove::Superko`]; convert
+/// with [`BitBoard::to_board`] when that is needed.
+#[derive(Debug, Clone)]
+pub struct BitBoard {
+    black: Bits,
+    white: Bits,
+    /// Board size.
+    pub size: usize,
+    /// Number of the last move, as [`Board::move_number`].
+    pub move_number: usize,
+    /// Black stones captured by white.
+    pub captured_white: u16,
+    /// White stones captured by black.
+    pub captured_black: u16,
+    /// Forbidden point for simple ko rule.
+    pub ko_point: Option<(usize, usize)>,
+    /// Side to move next.
+    pub to_play: Cell,
+    /// Ruleset from the `RU` property.
+    pub rules: Rules,
+    /// Komi from the `KM` property.
+    pub komi: f64,
+    /// Zobrist hash of the stones, equal to [`Board::hash`] for the same
+    /// position.
+    pub hash: u64,
+}
+
+impl Default for BitBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BitBoard {
+    /// An empty 19×19 board with black to play.
+    pub fn new() -> Self {
+        Self {
+            black: Bits::EMPTY,
+            white: Bits::EMPTY,
+            size: 19,
+            move_number: 0,
+            captured_white: 0,
+            captured_black: 0,
+            ko_point: None,
+            to_play: Cell::Black,
+            rules: Rules::default(),
+            komi: 0.0,
+            hash: 0,
+        }
+    }
+
+    /// Build the position at `cursor` in `tree`, like [`Board::from_tree`].
+    pub fn from_tree(tree: &GameTree, cursor: NodeId) -> Self {
+        let mut board = Self::new();
+        let mut path: Vec<NodeId> = tree.iter_ancestors(cursor).map(|(id, _)| id).collect();
+        path.reverse();
+        for id in path {
+            board.apply_node(tree.node(id));
+        }
+        board
+    }
+
+    /// Apply a single tree node's properties to this position.
+    pub fn apply_node(&mut self, node: &TreeNode) {
+        replay_node(self, node);
+    }
+
+    /// The occupant of `(row, col)`.
+    pub fn cell(&self, row: usize, col: usize) -> Cell {
+        if self.black.contains(row, col) {
+            Cell::Black
+        } else if self.white.contains(row, col) {
+            Cell::White
+        } else {
+            Cell::Empty
+        }
+    }
+
+    /// All stones of `color`, or all empty points for [`Cell::Empty`].
+    pub fn stones(&self, color: Cell) -> Bits {
+        match color {
+            Cell::Black => self.black,
+            Cell::White => self.white,
+            Cell::Empty => self.empty_points(),
+        }
+    }
+
+    /// Every empty point on the board.
+    pub fn empty_points(&self) -> Bits {
+        Bits::full(self.size) - self.black - self.white
+    }
+
+    /// The connected group of same-coloured stones containing `(row, col)`;
+    /// empty if the point is empty.
+    pub fn find_group(&self, row: usize, col: usize) -> Bits {
+        let color = self.cell(row, col);
+        if color == Cell::Empty {
+            return Bits::EMPTY;
+        }
+        let stones = self.stones(color);
+        let mut group = Bits::single(row, col);
+        loop {
+            let grown = group.dilate(self.size) & stones;
+            if grown == group {
+                return group;
+            }
+            group = grown;
+        }
+    }
+
+    /// The empty points adjacent to `group`.
+    pub fn liberties(&self, group: &Bits) -> Bits {
+        group.dilate(self.size) & self.empty_points()
+    }
+
+    /// Number of liberties of `group`.
+    pub fn count_liberties(&self, group: &Bits) -> usize {
+        self.liberties(group).len()
+    }
+
+    /// Play `color` at `coord` (which may be a pass) after checking that
+    /// the move is legal, like [`Board::play`] but without the superko
+    /// check.  On failure the position is unchanged.
+    pub fn play(&mut self, color: Cell, coord: GoCoord) -> Result<MoveOutcome, IllegalMove> {
+        if color == Cell::Empty {
+            return Err(IllegalMove::NoStone);
+        }
+        if !coord.is_pass() {
+            self.check_move(coord_row(coord), coord_col(coord), color)?;
+        }
+        Ok(self.apply_move(color, coord, None))
+    }
+
+    /// Return `true` if `color` may play at `(row, col)`, like
+    /// [`Board::is_legal`] but without the superko check.
+    pub fn is_legal(&self, row: usize, col: usize, color: Cell) -> bool {
+        color != Cell::Empty && self.check_move(row, col, color).is_ok()
+    }
+
+    /// Analyse the position into chains, numbered and ordered as by
+    /// [`Board::chains`].
+    pub fn chains(&self) -> ChainMap {
+        let mut chains = Vec::new();
+        let mut seen = Bits::EMPTY;
+        for (row, col) in (self.black | self.white).iter() {
+            if seen.contains(row, col) {
+                continue;
+            }
+            let group = self.find_group(row, col);
+            seen = seen | group;
+            chains.push(Chain {
+                color: self.cell(row, col),
+                stones: group.iter().collect(),
+                liberties: self.liberties(&group).iter().collect(),
+                enemies: Vec::new(),
+            });
+        }
+        ChainMap::new(chains, self.size)
+    }
+
+    /// Convert to a [`Board`] with the same stones and counters.  The
+    /// result has no superko history and no per-point move numbers.
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new();
+        for row in 0..self.size {
+            for col in 0..self.size {
+                board.cells[row][col] = self.cell(row, col);
+            }
+        }
+        board.rehash();
+        board.size = self.size;
+        board.move_number = self.move_number;
+        board.captured_white = self.captured_white;
+        board.captured_black = self.captured_black;
+        board.ko_point = self.ko_point;
+        board.to_play = self.to_play;
+        board.rules = self.rules;
+        board.komi = self.komi;
+        board
+    }
+
+    fn check_move(&self, row: usize, col: usize, color: Cell) -> Result<(), IllegalMove> {
+        if row >= self.size || col >= self.size {
+            return Err(IllegalMove::OffBoard);
+        }
+        if self.cell(row, col) != Cell::Empty {
+            return Err(IllegalMove::Occupied);
+        }
+        if self.ko_point == Some((row, col)) {
+            return Err(IllegalMove::Ko);
+        }
+        if !self.rules.allows_suicide() && self.is_suicide(row, col, color) {
+            return Err(IllegalMove::Suicide);
+        }
+        Ok(())
+    }
+
+    /// Return `true` if a stone of `color` at `(row, col)` would be left
+    /// without liberties once opponent captures are resolved.
+    fn is_suicide(&self, row: usize, col: usize, color: Cell) -> bool {
+        let mut next = self.clone();
+        // Keep a suicided group on the board so its liberties can be counted.
+        next.rules = Rules::Japanese;
+        next.set(row, col, color);
+        next.apply_captures(row, col, color);
+        next.count_liberties(&next.find_group(row, col)) == 0
+    }
+
+    fn apply_move(&mut self, color: Cell, coord: GoCoord, number: Option<usize>) -> MoveOutcome {
+        let outcome = if coord.is_pass() {
+            self.ko_point = None;
+            MoveOutcome::default()
+        } else {
+            let (row, col) = (coord_row(coord), coord_col(coord));
+            self.set(row, col, color);
+            let outcome = self.apply_captures(row, col, color);
+            self.ko_point = outcome.ko_point;
+            outcome
+        };
+        self.move_number = number.unwrap_or(self.move_number + 1);
+        self.to_play = match color {
+            Cell::White => Cell::Black,
+            _ => Cell::White,
+        };
+        outcome
+    }
+
+    fn set(&mut self, row: usize, col: usize, cell: Cell) {
+        self.hash ^= zobrist::stone_key(row, col, self.cell(row, col));
+        self.hash ^= zobrist::stone_key(row, col, cell);
+        self.black.remove(row, col);
+        self.white.remove(row, col);
+        match cell {
+            Cell::Black => self.black.insert(row, col),
+            Cell::White => self.white.insert(row, col),
+            Cell::Empty => {}
+        }
+    }
+
+    fn remove_group(&mut self, group: &Bits) {
+        for (row, col) in group.iter() {
+            self.set(row, col, Cell::Empty);
+        }
+    }
+
+    /// Remove opponent groups left without liberties by a stone of `color`
+    /// at `(row, col)`, then the mover's own group if it has none and the
+    /// rules allow suicide.
+    fn apply_captures(&mut self, row: usize, col: usize, color: Cell) -> MoveOutcome {
+        let opponent = match color {
+            Cell::White => Cell::Black,
+            _ => Cell::White,
+        };
+        let mut outcome = MoveOutcome::default();
+
+        let mut adjacent = Bits::single(row, col).dilate(self.size) & self.stones(opponent);
+        while let Some((r, c)) = adjacent.first() {
+            let group = self.find_group(r, c);
+            adjacent = adjacent - group;
+            if self.count_liberties(&group) > 0 {
+                continue;
+            }
+            let n = group.len();
+            outcome.captured.extend(group.iter());
+            self.remove_group(&group);
+            match opponent {
+                Cell::Black => self.captured_white += n as u16,
+                _ => self.captured_black += n as u16,
+            }
+        }
+
+        let placed = self.find_group(row, col);
+        let liberties = self.count_liberties(&placed);
+        if liberties == 0 && self.rules.allows_suicide() {
+            let n = placed.len() as u16;
+            self.remove_group(&placed);
+            outcome.self_captured = placed.iter().collect();
+            match color {
+                Cell::Black => self.captured_white += n,
+                _ => self.captured_black += n,
+            }
+            return outcome;
+        }
+
+        if outcome.captured.len() == 1 && liberties == 1 {
+            outcome.ko_point = Some(outcome.captured[0]);
+        }
+        outcome
+    }
+}
+
+impl From<&Board> for BitBoard {
+    fn from(board: &Board) -> Self {
+        let mut bits = BitBoard {
+            size: board.size,
+            move_number: board.move_number,
+            captured_white: board.captured_white,
+            captured_black: board.captured_black,
+            ko_point: board.ko_point,
+            to_play: board.to_play,
+            rules: board.rules,
+            komi: board.komi,
+            ..BitBoard::new()
+        };
+        for row in 0..board.size {
+            for col in 0..board.size {
+                bits.set(row, col, board.cells[row][col]);
+            }
+        }
+        bits
+    }
+}
+
+impl Replay for BitBoard {
+    fn replay_move(&mut self, color: Cell, coord: GoCoord, number: Option<usize>) {
+        self.apply_move(color, coord, number);
+    }
+
+    fn replay_setup(&mut self, row: usize, col: usize, color: Cell) {
+        self.set(row, col, color);
+    }
+
+    fn settings(&mut self) -> Settings<'_> {
+        Settings {
+            size: &mut self.size,
+            komi: &mut self.komi,
+            rules: &mut self.rules,
+            to_play: &mut self.to_play,
+            ko_point: &mut self.ko_point,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{count_liberties, find_group, parse_sgf};
+
+    const GAME: &str = include_str!("../../benches/game.sgf");
+
+    #[test]
+    fn replay_matches_board() {
+        for sgf in [
+            GAME,
+            "(;AW[ff][ee][eg][df]AB[fe][fg][gf];B[ef])",
+            "(;RU[NZ]AB[aa][ba]AW[ab][bb][da][cb];B[ca])",
+            "(;SZ[9]AB[ih]AW[hh][ig];B[ii]MN[7];W[ai])",
+        ] {
+            let tree = parse_sgf(sgf).unwrap();
+            for (id, _) in tree.iter_subtree(tree.roots[0]) {
+                let board = Board::from_tree(&tree, id);
+                let bits = BitBoard::from_tree(&tree, id);
+                for row in 0..19 {
+                    for col in 0..19 {
+                        assert_eq!(bits.cell(row, col), board.cells[row][col]);
+                    }
+                }
+                assert_eq!(bits.hash, board.hash);
+                assert_eq!(bits.size, board.size);
+                assert_eq!(bits.ko_point, board.ko_point);
+                assert_eq!(bits.move_number, board.move_number);
+                assert_eq!(
+                    (bits.captured_black, bits.captured_white),
+                    (board.captured_black, board.captured_white)
+                );
+                assert_eq!(bits.to_board().cells, board.cells);
+            }
+        }
+    }
+
+    #[test]
+    fn play_and_chains_match_board() {
+        for sgf in [
+            GAME,
+            "(;AW[ff][ee][eg][df]AB[fe][fg][gf];B[ef])",
+            "(;AB[aa][ba]AW[ab][bb][da][cb])",
+            "(;RU[NZ]AB[aa][ba]AW[ab][bb][da][cb])",
+        ] {
+            let tree = parse_sgf(sgf).unwrap();
+            let leaf = tree.iter_leaves(tree.roots[0]).next().unwrap().0;
+            let board = Board::from_tree(&tree, leaf);
+            let bits = BitBoard::from(&board);
+            assert_eq!(bits.chains().chains(), board.chains().chains());
+            for row in 0..19 {
+                for col in 0..19 {
+                    for color in [Cell::Black, Cell::White] {
+                        let coord = GoCoord::from_colrow(col, row);
+                        let (mut b, mut bb) = (board.clone(), bits.clone());
+                        let expected = b.play(color, coord).map(|o| o.captured.len());
+                        assert_eq!(bb.play(color, coord).map(|o| o.captured.len()), expected);
+                        assert_eq!(bits.is_legal(row, col, color), expected.is_ok());
+                        if expected.is_ok() {
+                            assert_eq!(bb.to_board().cells, b.cells);
+                            assert_eq!(bb.ko_point, b.ko_point);
+                        }
+                    }
+                }
+            }
+        }
+        let mut bits = BitBoard::new();
+        assert_eq!(
+            bits.play(Cell::Empty, GoCoord::pass()),
+            Err(IllegalMove::NoStone)
+        );
+        assert_eq!(
+            bits.play(Cell::Black, GoCoord::from_colrow(0, 19)),
+            Err(IllegalMove::OffBoard)
+        );
+    }
+
+    #[test]
+    fn groups_and_liberties_match_helpers() {
+        let tree = parse_sgf(GAME).unwrap();
+        let leaf = tree.iter_leaves(tree.roots[0]).next().unwrap().0;
+        let board = Board::from_tree(&tree, leaf);
+        let bits = BitBoard::from(&board);
+        for row in 0..19 {
+            for col in 0..19 {
+                if board.cells[row][col] == Cell::Empty {
+                    assert!(bits.find_group(row, col).is_empty());
+                    continue;
+                }
+                let mut group = find_group(&board.cells, row, col, 19);
+                group.sort_unstable();
+                let set = bits.find_group(row, col);
+                assert_eq!(set.iter().collect::<Vec<_>>(), group);
+                assert_eq!(
+                    bits.count_liberties(&set),
+                    count_liberties(&board.cells, &group, 19)
+                );
+            }
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use std::fmt::{Display, Formatter};
+
 use crate::sgf::{
-    node::SGFProperty,
+    node::{GoCoord, SGFProperty},
+    rules::Rules,
     tree::{GameTree, NodeId, TreeNode},
+    zobrist,
 };
 
 /// The occupancy state of a single board intersection.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Cell {
     Empty,
     Black,
@@ -12,7 +16,7 @@ pub enum Cell {
 }
 
 impl Cell {
-    fn opposite(self) -> Cell {
+    pub(crate) fn opposite(self) -> Cell {
         match self {
             Cell::Empty => Cell::Empty,
             Cell::Black => Cell::White,
@@ -39,6 +43,10 @@ impl Neighbors {
 /// Typical usage is something like:
 ///
 /// ```rust
+/// # use tesuji::{Editor, parse_sgf};
+/// # use tesuji::sgf::Board;
+/// # fn render_board(_: &Board) {}
+/// # let editor = Editor::new(parse_sgf("(;GM[1];B[dd])").unwrap());
 /// // After every EditCommand that moves the cursor or modifies the tree, recompute the board and redraw:
 /// let board = Board::from_tree(&editor.tree, editor.cursor);
 /// render_board(&board);
@@ -47,10 +55,12 @@ pub struct Board {
     /// `cells[row][col]`, 0-indexed from top-left.
     pub cells: [[Cell; 19]; 19],
 
-    /// Number of moves played to reach this position (not counting setup stones)
+    /// Number of the last move played to reach this position, not counting
+    /// setup stones.  Counts up by one per `B`/`W` node, except that an `MN`
+    /// property sets the number of its node's move.
     pub move_number: usize,
 
-    /// Board size
+    /// Board size, from the root's `SZ` (19 when absent)
     pub size: usize,
 
     /// Black stones captured by white
@@ -61,6 +71,101 @@ pub struct Board {
 
     /// Forbidden point for simple ko rule. `None` when no active ko.
     pub ko_point: Option<(usize, usize)>,
+
+    /// Side to move next: the opposite of the last move's colour, or the
+    /// colour named by a `PL` property.  [`Cell::Black`] before any move.
+    pub to_play: Cell,
+
+    /// Zobrist hash of the stones on the board, kept up to date
+    /// incrementally by [`Board::apply_node`].  Writing to `cells` directly
+    /// bypasses it; call [`Board::rehash`] afterwards.
+    pub hash: u64,
+
+    /// `(hash, to_play)` after every node along the path that played a move
+    /// or placed setup stones, oldest first.  Used for superko checks.
+    pub history: Vec<(u64, Cell)>,
+
+    /// Ruleset taken from the `RU` property, deciding whether suicide
+    /// removes the played group.  [`Rules::Japanese`] when absent.
+    pub rules: Rules,
+
+    /// Komi from the `KM` property, or 0 when absent.
+    pub komi: f64,
+
+    /// The move number each stone was played as, `None` for setup stones
+    /// and empty points.  Read it with [`Board::move_number_at`].
+    pub(crate) move_numbers: [[Option<u16>; 19]; 19],
+
+    /// When set, every cell change is recorded as `(row, col, previous,
+    /// previous move number)` so that [`BoardState`](crate::sgf::BoardState)
+    /// can undo it.
+    journal: Option<Vec<CellChange>>,
+}
+
+/// A journaled cell change: `(row, col, previous cell, previous move
+/// number)`.
+pub(crate) type CellChange = (usize, usize, Cell, Option<u16>);
+
+/// Which repetitions count as a superko violation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuperkoRule {
+    /// A move may not recreate any earlier arrangement of stones.
+    Positional,
+    /// A move may not recreate an earlier arrangement of stones with the
+    /// same side to move.
+    Situational,
+}
+
+/// What a legal move did to the board; see [`Board::play`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MoveOutcome {
+    /// Opponent stones removed by the move, as `(row, col)`.
+    pub captured: Vec<(usize, usize)>,
+    /// The mover's own stones removed by a suicide the rules allow.
+    pub self_captured: Vec<(usize, usize)>,
+    /// The point the opponent may not retake immediately, if the move
+    /// started a simple ko.
+    pub ko_point: Option<(usize, usize)>,
+}
+
+/// Why [`Board::play`] rejected a move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalMove {
+    /// The colour to play is [`Cell::Empty`].
+    NoStone,
+    /// The coordinate lies outside the board.
+    OffBoard,
+    /// The intersection already holds a stone.
+    Occupied,
+    /// The move retakes a simple ko immediately.
+    Ko,
+    /// The move would capture the mover's own group and the rules forbid it.
+    Suicide,
+    /// The move recreates an earlier position and breaks the rules' superko
+    /// rule.
+    Superko,
+}
+
+impl Display for IllegalMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            IllegalMove::NoStone => "no stone colour to play",
+            IllegalMove::OffBoard => "off the board",
+            IllegalMove::Occupied => "intersection is occupied",
+            IllegalMove::Ko => "ko",
+            IllegalMove::Suicide => "suicide",
+            IllegalMove::Superko => "superko",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Clone for Board {
@@ -72,24 +177,43 @@ impl Clone for Board {
             captured_white: self.captured_white,
             captured_black: self.captured_black,
             ko_point: self.ko_point,
+            to_play: self.to_play,
+            hash: self.hash,
+            history: self.history.clone(),
+            rules: self.rules,
+            komi: self.komi,
+            move_numbers: self.move_numbers,
+            journal: None,
         }
     }
 }
 
 impl Board {
-    /// Build a board snapshot for the position at `cursor` in `tree`.
-    ///
-    /// If `tree` is empty or `cursor` is out of range this returns an
-    /// empty board with `move_number = 0`.
-    pub fn from_tree(tree: &GameTree, cursor: NodeId) -> Self {
-        let mut board = Self {
+    /// An empty 19×19 board with black to play.
+    pub fn new() -> Self {
+        Self {
             cells: [[Cell::Empty; 19]; 19],
             move_number: 0,
             size: 19,
             captured_white: 0,
             captured_black: 0,
             ko_point: None,
-        };
+            to_play: Cell::Black,
+            hash: 0,
+            history: Vec::new(),
+            rules: Rules::default(),
+            komi: 0.0,
+            move_numbers: [[None; 19]; 19],
+            journal: None,
+        }
+    }
+
+    /// Build a board snapshot for the position at `cursor` in `tree`.
+    ///
+    /// If `tree` is empty or `cursor` is out of range this returns an
+    /// empty board with `move_number = 0`.
+    pub fn from_tree(tree: &GameTree, cursor: NodeId) -> Self {
+        let mut board = Self::new();
 
         // Get path from root -> cursor:
         // Follow parent ptr upwards -> push each node id onto a stack -> reverse stack
@@ -114,66 +238,248 @@ impl Board {
 
     /// Apply a single tree node's properties to this board position.
     pub fn apply_node(&mut self, node: &TreeNode) {
-        for prop in &node.properties {
-            match prop {
-                SGFProperty::B(coord) => {
-                    if !coord.is_pass() {
-                        let row = coord.second() as usize - b'a' as usize;
-                        let col = coord.first() as usize - b'a' as usize;
-                        self.cells[row][col] = Cell::Black;
-                        self.ko_point = self.apply_captures(row, col, Cell::Black);
-                    } else {
-                        self.ko_point = None;
-                    }
-                    self.move_number += 1;
-                }
-                SGFProperty::W(coord) => {
-                    if !coord.is_pass() {
-                        let row = coord.second() as usize - b'a' as usize;
-                        let col = coord.first() as usize - b'a' as usize;
-                        self.cells[row][col] = Cell::White;
-                        self.ko_point = self.apply_captures(row, col, Cell::White);
-                    } else {
-                        self.ko_point = None;
-                    }
-                    self.move_number += 1;
-                }
-                // Do not increment move counter for setup stones
-                // and clear ko point
-                SGFProperty::AB(coords) => {
-                    self.ko_point = None;
-                    for coord in coords {
-                        let row = coord.second() as usize - b'a' as usize;
-                        let col = coord.first() as usize - b'a' as usize;
-                        self.cells[row][col] = Cell::Black;
-                    }
-                }
-                SGFProperty::AW(coords) => {
-                    self.ko_point = None;
-                    for coord in coords {
-                        let row = coord.second() as usize - b'a' as usize;
-                        let col = coord.first() as usize - b'a' as usize;
-                        self.cells[row][col] = Cell::White;
-                    }
-                }
-                _ => {}
+        if replay_node(self, node) {
+            self.history.push((self.hash, self.to_play));
+        }
+    }
+
+    /// Play `color` at `coord` (which may be a pass) after checking that
+    /// the move is legal under [`Board::rules`].
+    ///
+    /// On success the board is updated exactly as [`Board::apply_node`]
+    /// would for a `B`/`W` node.  On failure the board is unchanged.
+    pub fn play(&mut self, color: Cell, coord: GoCoord) -> Result<MoveOutcome, IllegalMove> {
+        if color == Cell::Empty {
+            return Err(IllegalMove::NoStone);
+        }
+        if !coord.is_pass() {
+            self.check_move(coord_row(coord), coord_col(coord), color)?;
+        }
+        let outcome = self.apply_move(color, coord, None);
+        self.history.push((self.hash, self.to_play));
+        Ok(outcome)
+    }
+
+    /// Play `color` at `coord` without any legality check, advancing the
+    /// move counter (or setting it to `number`, from `MN`) and side to move.
+    /// Does not record history.
+    fn apply_move(&mut self, color: Cell, coord: GoCoord, number: Option<usize>) -> MoveOutcome {
+        self.move_number = number.unwrap_or(self.move_number + 1);
+        let outcome = if coord.is_pass() {
+            self.ko_point = None;
+            MoveOutcome::default()
+        } else {
+            let (row, col) = (coord_row(coord), coord_col(coord));
+            let outcome = self.place(row, col, color);
+            if self.cells[row][col] == color {
+                self.move_numbers[row][col] = Some(saturating_u16(self.move_number));
+            }
+            outcome
+        };
+        self.to_play = color.opposite();
+        outcome
+    }
+
+    /// The move number the stone on `(row, col)` was played as, honouring
+    /// `MN`.  `None` for empty points and for stones placed by setup
+    /// properties.  Numbers above `u16::MAX` are capped.
+    pub fn move_number_at(&self, row: usize, col: usize) -> Option<usize> {
+        if self.cells[row][col] == Cell::Empty {
+            return None;
+        }
+        self.move_numbers[row][col].map(usize::from)
+    }
+
+    /// Every stone on the board with the move number it was played as, in
+    /// row-major order.
+    pub fn numbered_stones(&self) -> impl Iterator<Item = ((usize, usize), usize)> + '_ {
+        (0..self.size).flat_map(move |row| {
+            (0..self.size).filter_map(move |col| {
+                self.move_number_at(row, col)
+                    .map(|number| ((row, col), number))
+            })
+        })
+    }
+
+    /// Put a stone of `color` at `(row, col)` and resolve captures, updating
+    /// the ko point.  Does not touch the move counter or side to move.
+    fn place(&mut self, row: usize, col: usize, color: Cell) -> MoveOutcome {
+        self.set_cell(row, col, color);
+        let outcome = self.apply_captures(row, col, color);
+        self.ko_point = outcome.ko_point;
+        outcome
+    }
+
+    /// Return `true` if playing `color` at `(row, col)` would leave the
+    /// played group without liberties once opponent captures are resolved.
+    pub fn is_suicide(&self, row: usize, col: usize, color: Cell) -> bool {
+        let mut cells = self.cells;
+        cells[row][col] = color;
+        let opponent = color.opposite();
+
+        for &(nr, nc) in orthogonal_neighbors(row, col, self.size).as_slice() {
+            if cells[nr][nc] != opponent {
+                continue;
+            }
+            let group = find_group(&cells, nr, nc, self.size);
+            if count_liberties(&cells, &group, self.size) == 0 {
+                // Capturing anything always leaves the played stone a liberty.
+                return false;
+            }
+        }
+        let group = find_group(&cells, row, col, self.size);
+        count_liberties(&cells, &group, self.size) == 0
+    }
+
+    /// Return `true` if `color` may play at `(row, col)` under
+    /// [`Board::rules`].  See [`Board::play`] for the reasons a move can be
+    /// illegal.
+    pub fn is_legal(&self, row: usize, col: usize, color: Cell) -> bool {
+        color != Cell::Empty && self.check_move(row, col, color).is_ok()
+    }
+
+    fn check_move(&self, row: usize, col: usize, color: Cell) -> Result<(), IllegalMove> {
+        if row >= self.size || col >= self.size {
+            return Err(IllegalMove::OffBoard);
+        }
+        if self.cells[row][col] != Cell::Empty {
+            return Err(IllegalMove::Occupied);
+        }
+        if self.ko_point == Some((row, col)) {
+            return Err(IllegalMove::Ko);
+        }
+        if self.is_suicide(row, col, color) && !self.rules.allows_suicide() {
+            return Err(IllegalMove::Suicide);
+        }
+        // Single-stone suicide would be a no-op, but no ruleset allows
+        // suicide without superko.
+        if let Some(rule) = self.rules.superko() {
+            let next = situational_hash(self.hash_after(row, col, color), color.opposite(), rule);
+            if self
+                .history
+                .iter()
+                .any(|&(h, p)| situational_hash(h, p, rule) == next)
+            {
+                return Err(IllegalMove::Superko);
+            }
+        }
+        Ok(())
+    }
+
+    /// [`Board::hash`] after `color` plays at `(row, col)`, worked out from
+    /// the placed stone and the chains it would remove instead of playing
+    /// the move.
+    fn hash_after(&self, row: usize, col: usize, color: Cell) -> u64 {
+        let mut cells = self.cells;
+        let mut hash = self.hash ^ zobrist::stone_key(row, col, color);
+        cells[row][col] = color;
+        let mut remove = |cells: &mut [[Cell; 19]; 19], group: &[(usize, usize)]| {
+            for &(r, c) in group {
+                hash ^= zobrist::stone_key(r, c, cells[r][c]);
+                cells[r][c] = Cell::Empty;
+            }
+        };
+
+        let opponent = color.opposite();
+        for &(nr, nc) in orthogonal_neighbors(row, col, self.size).as_slice() {
+            if cells[nr][nc] != opponent {
+                continue;
+            }
+            let group = find_group(&cells, nr, nc, self.size);
+            if count_liberties(&cells, &group, self.size) == 0 {
+                remove(&mut cells, &group);
+            }
+        }
+        if self.rules.allows_suicide() {
+            let group = find_group(&cells, row, col, self.size);
+            if count_liberties(&cells, &group, self.size) == 0 {
+                remove(&mut cells, &group);
+            }
+        }
+        hash
+    }
+
+    /// Apply `node` like [`Board::apply_node`] and return every cell change
+    /// it made, in order.
+    pub(crate) fn apply_node_journaled(&mut self, node: &TreeNode) -> Vec<CellChange> {
+        self.journal = Some(Vec::new());
+        self.apply_node(node);
+        self.journal.take().unwrap_or_default()
+    }
+
+    /// Set the intersection at `(row, col)` to `cell`, keeping [`Board::hash`]
+    /// in sync.  The point loses its move number; `apply_move` sets
+    /// it again for a played stone.
+    pub(crate) fn set_cell(&mut self, row: usize, col: usize, cell: Cell) {
+        if let Some(journal) = &mut self.journal {
+            journal.push((row, col, self.cells[row][col], self.move_numbers[row][col]));
+        }
+        self.hash ^= zobrist::stone_key(row, col, self.cells[row][col]);
+        self.hash ^= zobrist::stone_key(row, col, cell);
+        self.cells[row][col] = cell;
+        self.move_numbers[row][col] = None;
+    }
+
+    /// Undo one journaled [`CellChange`].
+    pub(crate) fn restore_cell(&mut self, (row, col, cell, number): CellChange) {
+        self.set_cell(row, col, cell);
+        self.move_numbers[row][col] = number;
+    }
+
+    /// A copy of the position without its superko history (so superko is
+    /// not checked on it), for reading ahead or editing stones cheaply.
+    pub(crate) fn scratch(&self) -> Board {
+        Board {
+            history: Vec::new(),
+            journal: None,
+            ..*self
+        }
+    }
+
+    /// Recompute [`Board::hash`] from `cells`, e.g. after editing `cells`
+    /// directly.  History entries are left untouched.
+    pub fn rehash(&mut self) {
+        let mut hash = 0;
+        for row in 0..self.size {
+            for col in 0..self.size {
+                hash ^= zobrist::stone_key(row, col, self.cells[row][col]);
             }
         }
+        self.hash = hash;
+    }
+
+    /// Hash identifying the current position under `rule`: the stone hash,
+    /// plus the side to move for [`SuperkoRule::Situational`].
+    pub fn position_hash(&self, rule: SuperkoRule) -> u64 {
+        situational_hash(self.hash, self.to_play, rule)
+    }
+
+    /// Return `true` if the current position already occurred earlier on the
+    /// path, i.e. the last move broke the given superko rule.
+    ///
+    /// The current position is taken to be the last [`Board::history`]
+    /// entry.  A pass trivially repeats the position before it under
+    /// [`SuperkoRule::Positional`]; callers should only ask after a
+    /// non-pass move.
+    pub fn repeats_position(&self, rule: SuperkoRule) -> bool {
+        let Some((&(hash, to_play), earlier)) = self.history.split_last() else {
+            return false;
+        };
+        let current = situational_hash(hash, to_play, rule);
+        earlier
+            .iter()
+            .any(|&(h, p)| situational_hash(h, p, rule) == current)
     }
 
     /// Remove any opponent stones with zero liberties after placing a stone of
-    /// `color` at `(placed_row, placed_col)`.
+    /// `color` at `(placed_row, placed_col)`.  If the placed group is then
+    /// itself without liberties and [`Board::rules`] allow suicide, it is
+    /// removed too.
     ///
-    /// Returns the ko point if a simple ko arises or `None` otherwise.
-    fn apply_captures(
-        &mut self,
-        placed_row: usize,
-        placed_col: usize,
-        color: Cell,
-    ) -> Option<(usize, usize)> {
+    /// The outcome's ko point is set if a simple ko arises.
+    fn apply_captures(&mut self, placed_row: usize, placed_col: usize, color: Cell) -> MoveOutcome {
         let opponent = color.opposite();
-        let mut total_captured: usize = 0;
-        let mut last_captured_at: (usize, usize) = (0, 0);
+        let mut outcome = MoveOutcome::default();
 
         let nbrs = orthogonal_neighbors(placed_row, placed_col, self.size);
         for &(nr, nc) in nbrs.as_slice() {
@@ -188,13 +494,10 @@ impl Board {
 
             // Remove every stone in the group
             let n = group.len();
-            if n == 1 {
-                last_captured_at = group[0];
-            }
-            total_captured += n;
-            for (gr, gc) in group {
-                self.cells[gr][gc] = Cell::Empty;
+            for &(gr, gc) in &group {
+                self.set_cell(gr, gc, Cell::Empty);
             }
+            outcome.captured.extend(group);
             match opponent {
                 Cell::Black => self.captured_white += n as u16,
                 Cell::White => self.captured_black += n as u16,
@@ -202,16 +505,178 @@ impl Board {
             }
         }
 
+        let placed_group = find_group(&self.cells, placed_row, placed_col, self.size);
+        let liberties = count_liberties(&self.cells, &placed_group, self.size);
+
+        if liberties == 0 && self.rules.allows_suicide() {
+            let n = placed_group.len() as u16;
+            for &(gr, gc) in &placed_group {
+                self.set_cell(gr, gc, Cell::Empty);
+            }
+            outcome.self_captured = placed_group;
+            match color {
+                Cell::Black => self.captured_white += n,
+                Cell::White => self.captured_black += n,
+                Cell::Empty => {}
+            }
+            return outcome;
+        }
+
         // Simple ko
-        if total_captured == 1 {
-            let placed_group = find_group(&self.cells, placed_row, placed_col, self.size);
-            if count_liberties(&self.cells, &placed_group, self.size) == 1 {
-                return Some(last_captured_at);
+        if outcome.captured.len() == 1 && liberties == 1 {
+            outcome.ko_point = Some(outcome.captured[0]);
+        }
+
+        outcome
+    }
+}
+
+pub(crate) fn coord_row(coord: GoCoord) -> usize {
+    coord.second() as usize - b'a' as usize
+}
+
+pub(crate) fn coord_col(coord: GoCoord) -> usize {
+    coord.first() as usize - b'a' as usize
+}
+
+/// A position that SGF nodes can be replayed onto.  [`Board`] and
+/// [`BitBoard`](crate::sgf::BitBoard) store stones differently but share
+/// [`replay_node`], so they interpret nodes the same way.
+pub(crate) trait Replay {
+    /// Play `color` at `coord` (which may be a pass) without a legality
+    /// check, capturing as needed, advancing the move counter (or setting it
+    /// to `number`, from `MN`) and passing the turn.
+    fn replay_move(&mut self, color: Cell, coord: GoCoord, number: Option<usize>);
+    /// Put a setup stone of `color` on `(row, col)`.
+    fn replay_setup(&mut self, row: usize, col: usize, color: Cell);
+    /// The game settings that root and setup properties change.
+    fn settings(&mut self) -> Settings<'_>;
+}
+
+/// Mutable access to the fields of a [`Replay`] position that are not
+/// stones.
+pub(crate) struct Settings<'a> {
+    pub size: &'a mut usize,
+    pub komi: &'a mut f64,
+    pub rules: &'a mut Rules,
+    pub to_play: &'a mut Cell,
+    pub ko_point: &'a mut Option<(usize, usize)>,
+}
+
+impl Replay for Board {
+    fn replay_move(&mut self, color: Cell, coord: GoCoord, number: Option<usize>) {
+        self.apply_move(color, coord, number);
+    }
+
+    fn replay_setup(&mut self, row: usize, col: usize, color: Cell) {
+        self.set_cell(row, col, color);
+    }
+
+    fn settings(&mut self) -> Settings<'_> {
+        Settings {
+            size: &mut self.size,
+            komi: &mut self.komi,
+            rules: &mut self.rules,
+            to_play: &mut self.to_play,
+            ko_point: &mut self.ko_point,
+        }
+    }
+}
+
+/// Apply `node`'s moves (`B`, `W`, numbered by `MN`), setup stones (`AB`,
+/// `AW`) and `SZ`, `KM`, `RU` and `PL` to `position`.  Setup stones do not
+/// advance the move counter but clear the ko point.  Returns whether the
+/// node placed any stones or moves.
+pub(crate) fn replay_node(position: &mut impl Replay, node: &TreeNode) -> bool {
+    let mut changed = false;
+    let number = move_number_property(node);
+    for prop in &node.properties {
+        match prop {
+            SGFProperty::B(coord) => {
+                position.replay_move(Cell::Black, *coord, number);
+                changed = true;
+            }
+            SGFProperty::W(coord) => {
+                position.replay_move(Cell::White, *coord, number);
+                changed = true;
+            }
+            SGFProperty::AB(coords) | SGFProperty::AW(coords) => {
+                let color = match prop {
+                    SGFProperty::AB(_) => Cell::Black,
+                    _ => Cell::White,
+                };
+                *position.settings().ko_point = None;
+                for &coord in coords {
+                    position.replay_setup(coord_row(coord), coord_col(coord), color);
+                }
+                changed = true;
+            }
+            SGFProperty::SZ(size) => *position.settings().size = usize::from(*size).clamp(1, 19),
+            SGFProperty::KM(komi) => *position.settings().komi = komi.points(),
+            SGFProperty::Unknown(tag, values) if tag == "RU" => {
+                if let Some(rules) = values.first().and_then(|v| v.parse().ok()) {
+                    *position.settings().rules = rules;
+                }
+            }
+            SGFProperty::Unknown(tag, values) if tag == "PL" => {
+                match values.first().map(String::as_str) {
+                    Some("B") => *position.settings().to_play = Cell::Black,
+                    Some("W") => *position.settings().to_play = Cell::White,
+                    _ => {}
+                }
             }
+            _ => {}
         }
+    }
+    changed
+}
+
+/// The value of `node`'s `MN` property, if it has a valid one.
+pub(crate) fn move_number_property(node: &TreeNode) -> Option<usize> {
+    node.properties.iter().find_map(|prop| match prop {
+        SGFProperty::Unknown(tag, values) if tag == "MN" => {
+            values.first().and_then(|v| v.trim().parse().ok())
+        }
+        _ => None,
+    })
+}
+
+fn saturating_u16(n: usize) -> u16 {
+    u16::try_from(n).unwrap_or(u16::MAX)
+}
+
+fn situational_hash(hash: u64, to_play: Cell, rule: SuperkoRule) -> u64 {
+    match (rule, to_play) {
+        (SuperkoRule::Situational, Cell::White) => hash ^ zobrist::WHITE_TO_PLAY,
+        _ => hash,
+    }
+}
 
-        None
+/// Find every non-pass move in `tree` that recreates an earlier position on
+/// its own path under `rule`, in DFS pre-order.
+///
+/// Each node's board is carried down to its children, so the whole tree is
+/// replayed once.
+pub fn find_superko_violations(tree: &GameTree, rule: SuperkoRule) -> Vec<NodeId> {
+    let mut violations = Vec::new();
+    for &root in &tree.roots {
+        let mut stack = vec![(root, Board::from_tree(tree, root))];
+        while let Some((id, board)) = stack.pop() {
+            let is_move = tree.node(id).properties.iter().any(|p| match p {
+                SGFProperty::B(c) | SGFProperty::W(c) => !c.is_pass(),
+                _ => false,
+            });
+            if is_move && board.repeats_position(rule) {
+                violations.push(id);
+            }
+            for &child in tree.node(id).children.iter().rev() {
+                let mut next = board.clone();
+                next.apply_node(tree.node(child));
+                stack.push((child, next));
+            }
+        }
     }
+    violations
 }
 
 /// Returns valid orthogonally adjacent board positions to `(row, col)`
@@ -345,6 +810,17 @@ mod tests {
         assert_eq!(board.cells[15][3], Cell::White);
     }
 
+    #[test]
+    fn to_play_follows_last_move_and_pl() {
+        let tree = parse_sgf("(;AB[dd]PL[W];W[pp];B[dp])").unwrap();
+        let root = tree.roots[0];
+        assert_eq!(Board::from_tree(&tree, root).to_play, Cell::White);
+        let n1 = tree.node(root).children[0];
+        assert_eq!(Board::from_tree(&tree, n1).to_play, Cell::Black);
+        let n2 = tree.node(n1).children[0];
+        assert_eq!(Board::from_tree(&tree, n2).to_play, Cell::White);
+    }
+
     #[test]
     fn setup_stones_do_not_increment_move_number() {
         let tree = parse_sgf("(;AB[dd][pp]AW[dp])").unwrap();
@@ -356,6 +832,22 @@ mod tests {
         assert_eq!(board.ko_point, None);
     }
 
+    #[test]
+    fn stones_remember_their_move_numbers() {
+        let tree = parse_sgf("(;AB[ab][ba][bc];W[bb];B[pp]MN[10];W[pd];B[cb])").unwrap();
+        let last = tree.iter_mainline(tree.roots[0]).last().unwrap().0;
+        let board = Board::from_tree(&tree, last);
+        assert_eq!(board.move_number, 12);
+        assert_eq!(board.move_number_at(0, 1), None);
+        assert_eq!(board.move_number_at(1, 1), None);
+        assert_eq!(board.move_number_at(15, 15), Some(10));
+        assert_eq!(board.move_number_at(3, 15), Some(11));
+        assert_eq!(
+            board.numbered_stones().collect::<Vec<_>>(),
+            [((1, 2), 12), ((3, 15), 11), ((15, 15), 10)]
+        );
+    }
+
     #[test]
     fn capture_removes_surrounded_group() {
         let tree = parse_sgf("(;AW[bb]AB[ab][ba][bc];B[cb])").unwrap();
@@ -383,6 +875,109 @@ mod tests {
         assert_eq!(board.ko_point, None);
     }
 
+    #[test]
+    fn incremental_hash_matches_rehash() {
+        let tree = parse_sgf("(;AW[bb][dd]AB[ab][ba][bc][dc][cd][ed];B[cb];B[de];W[bb])").unwrap();
+        let mut id = tree.roots[0];
+        loop {
+            let mut board = Board::from_tree(&tree, id);
+            let incremental = board.hash;
+            board.rehash();
+            assert_eq!(board.hash, incremental);
+            match tree.node(id).children.first() {
+                Some(&c) => id = c,
+                None => break,
+            }
+        }
+    }
+
+    #[test]
+    fn superko_repetition_detected() {
+        // White immediately retakes the ko, recreating the setup position.
+        let tree =
+            parse_sgf("(;AW[ff][ee][eg][df]AB[fe][fg][gf];B[ef](;W[ff])(;W[ff]PL[W]))").unwrap();
+        let b = tree.node(tree.roots[0]).children[0];
+        let (retake, retake_pl) = (tree.node(b).children[0], tree.node(b).children[1]);
+
+        let board = Board::from_tree(&tree, retake);
+        assert!(board.repeats_position(SuperkoRule::Positional));
+        assert!(board.repeats_position(SuperkoRule::Situational));
+        assert!(!Board::from_tree(&tree, b).repeats_position(SuperkoRule::Positional));
+
+        assert_eq!(
+            find_superko_violations(&tree, SuperkoRule::Positional),
+            vec![retake, retake_pl]
+        );
+        assert_eq!(
+            find_superko_violations(&tree, SuperkoRule::Situational),
+            vec![retake]
+        );
+    }
+
+    #[test]
+    fn legality_follows_rules() {
+        // Black at ca would be a three-stone suicide.
+        let tree = parse_sgf("(;AB[aa][ba]AW[ab][bb][da][cb])").unwrap();
+        let mut board = Board::from_tree(&tree, tree.roots[0]);
+        assert!(board.is_suicide(0, 2, Cell::Black));
+        assert!(!board.is_legal(0, 2, Cell::Black));
+        assert!(!board.is_legal(0, 0, Cell::White));
+        assert!(board.is_legal(0, 2, Cell::White));
+
+        board.rules = Rules::NewZealand;
+        assert!(board.is_legal(0, 2, Cell::Black));
+        // Single-stone suicide recreates the position and breaks superko.
+        let tree = parse_sgf("(;RU[TT]AW[ab][ba])").unwrap();
+        let board = Board::from_tree(&tree, tree.roots[0]);
+        assert!(board.is_suicide(0, 0, Cell::Black));
+        assert!(!board.is_legal(0, 0, Cell::Black));
+    }
+
+    #[test]
+    fn hash_after_matches_the_played_position() {
+        // A capture, a plain move, and (under NZ rules) a three-stone suicide.
+        for (sgf, row, col, color) in [
+            ("(;AW[ff][ee][eg][df]AB[fe][fg][gf])", 5, 4, Cell::Black),
+            ("(;AB[dd]AW[pp])", 3, 15, Cell::White),
+            ("(;RU[NZ]AB[aa][ba]AW[ab][bb][da][cb])", 0, 2, Cell::Black),
+        ] {
+            let tree = parse_sgf(sgf).unwrap();
+            let board = Board::from_tree(&tree, tree.roots[0]);
+            let mut played = board.clone();
+            played.place(row, col, color);
+            assert_eq!(board.hash_after(row, col, color), played.hash, "{sgf}");
+        }
+    }
+
+    #[test]
+    fn play_reports_captures_and_errors() {
+        let tree = parse_sgf("(;AW[ff][ee][eg][df]AB[fe][fg][gf])").unwrap();
+        let mut board = Board::from_tree(&tree, tree.roots[0]);
+        let at = |s: &str| s.parse::<GoCoord>().unwrap();
+
+        let outcome = board.play(Cell::Black, at("ef")).unwrap();
+        assert_eq!(outcome.captured, vec![(5, 5)]);
+        assert_eq!(outcome.ko_point, Some((5, 5)));
+        assert_eq!(board.move_number, 1);
+        assert_eq!(board.to_play, Cell::White);
+
+        assert_eq!(board.play(Cell::White, at("ff")), Err(IllegalMove::Ko));
+        assert_eq!(
+            board.play(Cell::White, at("ef")),
+            Err(IllegalMove::Occupied)
+        );
+        assert_eq!(
+            board.play(Cell::White, GoCoord::from_colrow(0, 19)),
+            Err(IllegalMove::OffBoard)
+        );
+        assert_eq!(board.play(Cell::Empty, at("aa")), Err(IllegalMove::NoStone));
+        assert_eq!(board.move_number, 1);
+
+        board.play(Cell::White, GoCoord::pass()).unwrap();
+        assert_eq!(board.ko_point, None);
+        assert_eq!(board.to_play, Cell::Black);
+    }
+
     #[test]
     fn simple_ko_detected() {
         let tree = parse_sgf("(;AW[ff][ee][eg][df]AB[fe][fg][gf];B[ef])").unwrap();
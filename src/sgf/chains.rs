@@ -0,0 +1,215 @@
+use crate::sgf::{
+    Board, Cell, IllegalMove,
+    board::{coord_col, coord_row},
+    node::GoCoord,
+    orthogonal_neighbors,
+};
+
+/// Index of a [`Chain`] within a [`ChainMap`].
+pub type ChainId = usize;
+
+/// A maximal group of orthogonally connected stones of one colour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    pub color: Cell,
+    /// Member stones as `(row, col)`, in row-major order.
+    pub stones: Vec<(usize, usize)>,
+    /// Empty points adjacent to the chain, in row-major order.
+    pub liberties: Vec<(usize, usize)>,
+    /// Opponent chains touching this one, in ascending order.
+    pub enemies: Vec<ChainId>,
+}
+
+impl Chain {
+    /// Return `true` if the chain has exactly one liberty left.
+    pub fn in_atari(&self) -> bool {
+        self.liberties.len() == 1
+    }
+}
+
+/// Every chain on a [`Board`], with a point-to-chain lookup; see
+/// [`Board::chains`].
+#[derive(Debug, Clone)]
+pub struct ChainMap {
+    chains: Vec<Chain>,
+    ids: [[Option<ChainId>; 19]; 19],
+}
+
+impl ChainMap {
+    /// All chains, numbered in row-major order of their first stone.
+    pub fn chains(&self) -> &[Chain] {
+        &self.chains
+    }
+
+    pub fn chain(&self, id: ChainId) -> &Chain {
+        &self.chains[id]
+    }
+
+    /// The chain occupying `(row, col)`, or `None` for an empty point.
+    pub fn chain_at(&self, row: usize, col: usize) -> Option<ChainId> {
+        self.ids[row][col]
+    }
+
+    /// Index `chains` (numbered in row-major order of their first stone,
+    /// `enemies` left empty) by point and fill in each chain's enemies.
+    pub(crate) fn new(chains: Vec<Chain>, size: usize) -> Self {
+        let mut map = ChainMap {
+            chains,
+            ids: [[None; 19]; 19],
+        };
+        for (id, chain) in map.chains.iter().enumerate() {
+            for &(r, c) in &chain.stones {
+                map.ids[r][c] = Some(id);
+            }
+        }
+
+        for id in 0..map.chains.len() {
+            let mut enemies = Vec::new();
+            for &(r, c) in &map.chains[id].stones {
+                for &(nr, nc) in orthogonal_neighbors(r, c, size).as_slice() {
+                    if let Some(other) = map.ids[nr][nc]
+                        && map.chains[other].color != map.chains[id].color
+                        && !enemies.contains(&other)
+                    {
+                        enemies.push(other);
+                    }
+                }
+            }
+            enemies.sort_unstable();
+            map.chains[id].enemies = enemies;
+        }
+        map
+    }
+
+    /// Chains with exactly one liberty, optionally only those of `color`.
+    pub fn in_atari(&self, color: Option<Cell>) -> Vec<ChainId> {
+        self.chains
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.in_atari() && color.is_none_or(|color| c.color == color))
+            .map(|(id, _)| id)
+            .collect()
+    }
+}
+
+impl Board {
+    /// Analyse the position into chains with their liberties and adjacent
+    /// enemy chains.
+    pub fn chains(&self) -> ChainMap {
+        let size = self.size;
+        let mut chains = Vec::new();
+        let mut seen = [[false; 19]; 19];
+
+        for row in 0..size {
+            for col in 0..size {
+                let color = self.cells[row][col];
+                if color == Cell::Empty || seen[row][col] {
+                    continue;
+                }
+                let mut stones = Vec::new();
+                let mut liberties = Vec::new();
+                let mut seen_liberty = [[false; 19]; 19];
+                let mut stack = vec![(row, col)];
+                seen[row][col] = true;
+                while let Some((r, c)) = stack.pop() {
+                    stones.push((r, c));
+                    for &(nr, nc) in orthogonal_neighbors(r, c, size).as_slice() {
+                        let cell = self.cells[nr][nc];
+                        if cell == color && !seen[nr][nc] {
+                            seen[nr][nc] = true;
+                            stack.push((nr, nc));
+                        } else if cell == Cell::Empty && !seen_liberty[nr][nc] {
+                            seen_liberty[nr][nc] = true;
+                            liberties.push((nr, nc));
+                        }
+                    }
+                }
+                stones.sort_unstable();
+                liberties.sort_unstable();
+                chains.push(Chain {
+                    color,
+                    stones,
+                    liberties,
+                    enemies: Vec::new(),
+                });
+            }
+        }
+        ChainMap::new(chains, size)
+    }
+
+    /// The chains after `color` plays at `coord`, e.g. to ask which groups
+    /// a move puts in atari.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason if the move is illegal (see [`Board::play`]).
+    pub fn chains_after(&self, color: Cell, coord: GoCoord) -> Result<ChainMap, IllegalMove> {
+        let mut next = self.clone();
+        next.play(color, coord)?;
+        Ok(next.chains())
+    }
+
+    /// Return `true` if `color` playing at `coord` is legal but leaves the
+    /// played stone's chain in atari.
+    pub fn is_self_atari(&self, color: Cell, coord: GoCoord) -> bool {
+        if coord.is_pass() {
+            return false;
+        }
+        self.chains_after(color, coord).is_ok_and(|map| {
+            map.chain_at(coord_row(coord), coord_col(coord))
+                .is_some_and(|id| map.chain(id).in_atari())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.roots[0])
+    }
+
+    #[test]
+    fn chains_liberties_and_enemies() {
+        // Black pair at dd/ed, white stones at cd and dc.
+        let b = board("(;AB[dd][ed]AW[cd][dc])");
+        let map = b.chains();
+        assert_eq!(map.chains().len(), 3);
+
+        let black = map.chain_at(3, 3).unwrap();
+        assert_eq!(map.chain_at(3, 4), Some(black));
+        let chain = map.chain(black);
+        assert_eq!(chain.color, Cell::Black);
+        assert_eq!(chain.stones, vec![(3, 3), (3, 4)]);
+        assert_eq!(chain.liberties, vec![(2, 4), (3, 5), (4, 3), (4, 4)]);
+        assert_eq!(chain.enemies.len(), 2);
+        assert_eq!(map.chain_at(0, 0), None);
+        assert!(map.in_atari(None).is_empty());
+    }
+
+    #[test]
+    fn atari_and_self_atari() {
+        let b = board("(;AB[ba][ab]AW[ca][cb][bc][ac])");
+        let at = |s: &str| s.parse::<GoCoord>().unwrap();
+
+        // Black connecting at aa leaves the chain with only bb.
+        assert!(b.is_self_atari(Cell::Black, at("aa")));
+        // White at bb puts both black stones in atari without self-atari.
+        let map = b.chains_after(Cell::White, at("bb")).unwrap();
+        assert_eq!(map.in_atari(Some(Cell::Black)).len(), 2);
+        assert!(map.in_atari(Some(Cell::White)).is_empty());
+        assert!(!b.is_self_atari(Cell::White, at("bb")));
+        assert!(!b.is_self_atari(Cell::White, at("pp")));
+
+        // Afterwards black at aa would be suicide.
+        let mut after = b.clone();
+        after.play(Cell::White, at("bb")).unwrap();
+        assert_eq!(
+            after.chains_after(Cell::Black, at("aa")).unwrap_err(),
+            IllegalMove::Suicide
+        );
+    }
+}
@@ -0,0 +1,294 @@
+use std::fmt::{Display, Formatter};
+
+use crate::sgf::{GameTree, NodeId, SGFProperty, merge::node_key};
+
+/// What changed at one aligned position of two trees.
+#[derive(Debug, Clone)]
+pub enum Change {
+    /// A variation (the node and its whole subtree) exists only in the new tree.
+    AddedVariation { nodes: usize },
+    /// A variation exists only in the old tree.
+    RemovedVariation { nodes: usize },
+    /// A property present only in the new node.
+    PropertyAdded(SGFProperty),
+    /// A property present only in the old node.
+    PropertyRemoved(SGFProperty),
+    /// A property whose value differs between the two nodes.
+    PropertyChanged { old: SGFProperty, new: SGFProperty },
+    /// The node comment (`C`) was added, removed or edited.
+    CommentChanged {
+        old: Option<String>,
+        new: Option<String>,
+    },
+}
+
+/// A single difference, located by game record and move.
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    /// Index of the game record (0-based).
+    pub game: usize,
+    /// Number of moves from the root to the node (0 for the root).
+    pub move_number: usize,
+    /// The node's move (e.g. `B[dd]`), if it plays one.
+    pub node_move: Option<String>,
+    /// The node in the old tree, if it exists there.
+    pub old: Option<NodeId>,
+    /// The node in the new tree, if it exists there.
+    pub new: Option<NodeId>,
+    pub change: Change,
+}
+
+/// Semantic differences between two game trees, as produced by [`diff_trees`].
+///
+/// The [`Display`] impl renders one line per change (plus indented lines for
+/// multi-line comments), suitable for printing in a terminal.
+#[derive(Debug, Clone, Default)]
+pub struct TreeDiff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl TreeDiff {
+    /// Return `true` if the two trees are semantically identical.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Compare two trees by aligning their move sequences.
+///
+/// Game records are paired by index.  Children are aligned by their move
+/// (or, for move-less nodes, their setup stones) rather than by position, so
+/// reordering variations is not reported as a change.  Aligned nodes are
+/// compared property by property, with comments reported separately.
+pub fn diff_trees(old: &GameTree, new: &GameTree) -> TreeDiff {
+    let mut diff = TreeDiff::default();
+    let games = old.roots.len().max(new.roots.len());
+    for game in 0..games {
+        match (old.roots.get(game), new.roots.get(game)) {
+            (Some(&o), Some(&n)) => {
+                let mut walker = Walker {
+                    old,
+                    new,
+                    game,
+                    out: &mut diff.entries,
+                };
+                walker.diff_nodes(o, n, 0);
+            }
+            (Some(&o), None) => diff.entries.push(DiffEntry {
+                game,
+                move_number: 0,
+                node_move: None,
+                old: Some(o),
+                new: None,
+                change: Change::RemovedVariation {
+                    nodes: old.iter_subtree(o).count(),
+                },
+            }),
+            (None, Some(&n)) => diff.entries.push(DiffEntry {
+                game,
+                move_number: 0,
+                node_move: None,
+                old: None,
+                new: Some(n),
+                change: Change::AddedVariation {
+                    nodes: new.iter_subtree(n).count(),
+                },
+            }),
+            (None, None) => unreachable!(),
+        }
+    }
+    diff
+}
+
+struct Walker<'a> {
+    old: &'a GameTree,
+    new: &'a GameTree,
+    game: usize,
+    out: &'a mut Vec<DiffEntry>,
+}
+
+impl Walker<'_> {
+    fn diff_nodes(&mut self, o: NodeId, n: NodeId, move_number: usize) {
+        let node_move = move_of(self.new, n);
+        let move_number = move_number + usize::from(node_move.is_some());
+        let game = self.game;
+        let entry = |change| DiffEntry {
+            game,
+            move_number,
+            node_move: node_move.clone(),
+            old: Some(o),
+            new: Some(n),
+            change,
+        };
+
+        let old_props = &self.old.node(o).properties;
+        let new_props = &self.new.node(n).properties;
+        let comment = |props: &[SGFProperty]| {
+            props.iter().find_map(|p| match p {
+                SGFProperty::C(s) => Some(s.clone()),
+                _ => None,
+            })
+        };
+        let (old_c, new_c) = (comment(old_props), comment(new_props));
+        let mut changes = Vec::new();
+        if old_c != new_c {
+            changes.push(Change::CommentChanged {
+                old: old_c,
+                new: new_c,
+            });
+        }
+        for op in old_props.iter().filter(|p| p.tag() != "C") {
+            match new_props.iter().find(|p| p.tag() == op.tag()) {
+                None => changes.push(Change::PropertyRemoved(op.clone())),
+                Some(np) if np.to_string() != op.to_string() => {
+                    changes.push(Change::PropertyChanged {
+                        old: op.clone(),
+                        new: np.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for np in new_props.iter().filter(|p| p.tag() != "C") {
+            if !old_props.iter().any(|p| p.tag() == np.tag()) {
+                changes.push(Change::PropertyAdded(np.clone()));
+            }
+        }
+        self.out.extend(changes.into_iter().map(entry));
+
+        // Align children by node identity; each new child is matched at most once.
+        let old_children = &self.old.node(o).children;
+        let new_children = &self.new.node(n).children;
+        let mut matched = vec![false; new_children.len()];
+        for &oc in old_children {
+            let key = node_key(self.old, oc);
+            let found = new_children
+                .iter()
+                .enumerate()
+                .position(|(i, &nc)| !matched[i] && node_key(self.new, nc) == key);
+            match found {
+                Some(i) => {
+                    matched[i] = true;
+                    self.diff_nodes(oc, new_children[i], move_number);
+                }
+                None => {
+                    let mv = move_of(self.old, oc);
+                    self.out.push(DiffEntry {
+                        game: self.game,
+                        move_number: move_number + usize::from(mv.is_some()),
+                        node_move: mv,
+                        old: Some(oc),
+                        new: None,
+                        change: Change::RemovedVariation {
+                            nodes: self.old.iter_subtree(oc).count(),
+                        },
+                    });
+                }
+            }
+        }
+        for (i, &nc) in new_children.iter().enumerate() {
+            if !matched[i] {
+                let mv = move_of(self.new, nc);
+                self.out.push(DiffEntry {
+                    game: self.game,
+                    move_number: move_number + usize::from(mv.is_some()),
+                    node_move: mv,
+                    old: None,
+                    new: Some(nc),
+                    change: Change::AddedVariation {
+                        nodes: self.new.iter_subtree(nc).count(),
+                    },
+                });
+            }
+        }
+    }
+}
+
+fn move_of(tree: &GameTree, id: NodeId) -> Option<String> {
+    tree.node(id)
+        .properties
+        .iter()
+        .find(|p| matches!(p, SGFProperty::B(_) | SGFProperty::W(_)))
+        .map(|p| p.to_string())
+}
+
+impl Display for DiffEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "game {}, ", self.game + 1)?;
+        match &self.node_move {
+            Some(mv) => write!(f, "move {} ({mv}): ", self.move_number)?,
+            None if self.move_number == 0 => write!(f, "root: ")?,
+            None => write!(f, "after move {}: ", self.move_number)?,
+        }
+        match &self.change {
+            Change::AddedVariation { nodes } => {
+                write!(f, "+ variation ({nodes} node{})", plural(*nodes))
+            }
+            Change::RemovedVariation { nodes } => {
+                write!(f, "- variation ({nodes} node{})", plural(*nodes))
+            }
+            Change::PropertyAdded(p) => write!(f, "+ {p}"),
+            Change::PropertyRemoved(p) => write!(f, "- {p}"),
+            Change::PropertyChanged { old, new } => write!(f, "~ {old} -> {new}"),
+            Change::CommentChanged { old, new } => {
+                write!(f, "comment changed")?;
+                for line in old.iter().flat_map(|s| s.lines()) {
+                    write!(f, "\n    - {line}")?;
+                }
+                for line in new.iter().flat_map(|s| s.lines()) {
+                    write!(f, "\n    + {line}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The suffix that makes "node" agree with `n`.
+fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+impl Display for TreeDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    #[test]
+    fn identical_trees_have_no_diff() {
+        let tree = parse_sgf("(;GM[1];B[pd](;W[dd])(;W[dp]))").unwrap();
+        assert!(diff_trees(&tree, &tree).is_empty());
+    }
+
+    #[test]
+    fn reordered_variations_are_not_changes() {
+        let old = parse_sgf("(;GM[1];B[pd](;W[dd])(;W[dp]))").unwrap();
+        let new = parse_sgf("(;GM[1];B[pd](;W[dp])(;W[dd]))").unwrap();
+        assert!(diff_trees(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn reports_variations_properties_and_comments() {
+        let old = parse_sgf("(;GM[1]KM[6.5];B[pd]C[old];W[dd])").unwrap();
+        let new = parse_sgf("(;GM[1]KM[7.5]PB[Alice];B[pd]C[new](;W[dp];B[pp])(;W[cc]))").unwrap();
+        let diff = diff_trees(&old, &new);
+        assert_eq!(
+            diff.to_string(),
+            "game 1, root: ~ KM[6.5] -> KM[7.5]\n\
+             game 1, root: + PB[Alice]\n\
+             game 1, move 1 (B[pd]): comment changed\n    - old\n    + new\n\
+             game 1, move 2 (W[dd]): - variation (1 node)\n\
+             game 1, move 2 (W[dp]): + variation (2 nodes)\n\
+             game 1, move 2 (W[cc]): + variation (1 node)\n"
+        );
+    }
+}
@@ -0,0 +1,414 @@
+use std::fmt::{Display, Formatter};
+
+use crate::sgf::{Board, Cell, Scoring, find_group, orthogonal_neighbors};
+
+/// Influence seeded on every stone before dilation.
+const STONE_INFLUENCE: i32 = 128;
+/// Bouzy's 5/21 operator: five dilations followed by twenty-one erosions.
+const DILATIONS: usize = 5;
+const EROSIONS: usize = 21;
+/// Stones within this many points (Manhattan distance) of a chain decide
+/// whether it looks dead.
+const NEARBY: usize = 3;
+
+type Influence = [[i32; 19]; 19];
+
+/// An instant, engine-free guess at the final position; see
+/// [`Board::estimate`].
+#[derive(Debug, Clone)]
+pub struct Estimate {
+    cells: [[Cell; 19]; 19],
+    influence: Influence,
+    /// Board size.
+    pub size: usize,
+    /// Stones judged dead, in row-major order.
+    pub dead: Vec<(usize, usize)>,
+    /// Black's projected points, counted under the board's rules.
+    pub black: f64,
+    /// White's projected points including komi.
+    pub white: f64,
+}
+
+impl Estimate {
+    /// The side expected to own `(row, col)`, or [`Cell::Empty`] if neither.
+    pub fn owner(&self, row: usize, col: usize) -> Cell {
+        match self.influence[row][col] {
+            v if v > 0 => Cell::Black,
+            v if v < 0 => Cell::White,
+            _ => Cell::Empty,
+        }
+    }
+
+    /// Ownership of `(row, col)` from `-1.0` (white) to `1.0` (black).
+    pub fn ownership(&self, row: usize, col: usize) -> f32 {
+        (self.influence[row][col] as f32 / STONE_INFLUENCE as f32).clamp(-1.0, 1.0)
+    }
+
+    /// Black's projected lead; negative when white is ahead.
+    pub fn margin(&self) -> f64 {
+        self.black - self.white
+    }
+
+    /// The projected result in `RE` form, e.g. `"B+3.5"`.
+    pub fn result(&self) -> String {
+        let margin = self.margin();
+        if margin > 0.0 {
+            format!("B+{}", margin)
+        } else if margin < 0.0 {
+            format!("W+{}", -margin)
+        } else {
+            "0".to_string()
+        }
+    }
+}
+
+/// One row per line: `X`/`O` for live stones, `x`/`o` for dead ones, `+`/`-`
+/// for black/white area and `.` for neutral points.
+impl Display for Estimate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in 0..self.size {
+            let line: Vec<&str> = (0..self.size)
+                .map(|col| {
+                    let dead = self.dead.contains(&(row, col));
+                    match (self.cells[row][col], dead, self.owner(row, col)) {
+                        (Cell::Black, false, _) => "X",
+                        (Cell::White, false, _) => "O",
+                        (Cell::Black, true, _) => "x",
+                        (Cell::White, true, _) => "o",
+                        (Cell::Empty, _, Cell::Black) => "+",
+                        (Cell::Empty, _, Cell::White) => "-",
+                        (Cell::Empty, _, Cell::Empty) => ".",
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    /// Estimate ownership and the final score with Bouzy's 5/21
+    /// dilation/erosion influence function.
+    ///
+    /// A chain is judged dead when it borders fewer than two eyes (empty
+    /// regions touching only its own colour) and the opponent's stones
+    /// within three points of it outnumber its own side's (the chain
+    /// included) two to one, unless [`Board::unconditional_life`] proves it
+    /// alive.  Dead chains are removed, influence is recomputed, and empty
+    /// areas still neutral but enclosed by a single side's influence are
+    /// given to that side.
+    /// The projected score uses [`Board::rules`], [`Board::komi`] and the
+    /// prisoners already taken.
+    ///
+    /// This is a heuristic meant for a quick look at unfinished or resigned
+    /// games; use [`Board::score`] once the dead stones are agreed.
+    pub fn estimate(&self) -> Estimate {
+        let size = self.size;
+        let life = self.unconditional_life();
+        let mut dead = Vec::new();
+        let mut seen = [[false; 19]; 19];
+        for row in 0..size {
+            for col in 0..size {
+                if self.cells[row][col] == Cell::Empty || seen[row][col] {
+                    continue;
+                }
+                let chain = find_group(&self.cells, row, col, size);
+                for &(r, c) in &chain {
+                    seen[r][c] = true;
+                }
+                if !life.is_alive(row, col) && self.chain_looks_dead(&chain) {
+                    dead.extend(chain);
+                }
+            }
+        }
+        dead.sort_unstable();
+
+        let mut cells = self.cells;
+        for &(r, c) in &dead {
+            cells[r][c] = Cell::Empty;
+        }
+        let mut influence = bouzy(&cells, size);
+        fill_enclosed(&mut influence, size);
+
+        let (mut black, mut white) = (0.0, self.komi);
+        for row in 0..size {
+            for col in 0..size {
+                // Under territory scoring, live stones are not points.
+                if self.rules.scoring() == Scoring::Territory && cells[row][col] != Cell::Empty {
+                    continue;
+                }
+                match influence[row][col] {
+                    v if v > 0 => black += 1.0,
+                    v if v < 0 => white += 1.0,
+                    _ => {}
+                }
+            }
+        }
+        if self.rules.scoring() == Scoring::Territory {
+            let dead_of = |color| {
+                dead.iter()
+                    .filter(|&&(r, c)| self.cells[r][c] == color)
+                    .count()
+            };
+            black += f64::from(self.captured_black) + dead_of(Cell::White) as f64;
+            white += f64::from(self.captured_white) + dead_of(Cell::Black) as f64;
+        }
+
+        Estimate {
+            cells: self.cells,
+            influence,
+            size,
+            dead,
+            black,
+            white,
+        }
+    }
+
+    fn chain_looks_dead(&self, chain: &[(usize, usize)]) -> bool {
+        let eyes = adjacent_regions(&self.cells, chain, self.size)
+            .iter()
+            .filter(|&&(own, opponent)| own > 0 && opponent == 0)
+            .count();
+        if eyes >= 2 {
+            return false;
+        }
+        let color = self.cells[chain[0].0][chain[0].1];
+        let (mut own, mut opponent) = (0, 0);
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let cell = self.cells[row][col];
+                let near = chain
+                    .iter()
+                    .any(|&(r, c)| r.abs_diff(row) + c.abs_diff(col) <= NEARBY);
+                if cell == Cell::Empty || !near {
+                    continue;
+                }
+                if cell == color {
+                    own += 1;
+                } else {
+                    opponent += 1;
+                }
+            }
+        }
+        opponent > 2 * own
+    }
+}
+
+/// For each empty region adjacent to `chain`, count the distinct stones of
+/// the chain's colour and of the opponent's colour bordering it.
+fn adjacent_regions(
+    cells: &[[Cell; 19]; 19],
+    chain: &[(usize, usize)],
+    size: usize,
+) -> Vec<(usize, usize)> {
+    let color = cells[chain[0].0][chain[0].1];
+    let mut seen = [[false; 19]; 19];
+    let mut regions = Vec::new();
+    for &(r, c) in chain {
+        for &(lr, lc) in orthogonal_neighbors(r, c, size).as_slice() {
+            if cells[lr][lc] != Cell::Empty || seen[lr][lc] {
+                continue;
+            }
+            let mut border = [[false; 19]; 19];
+            let (mut own, mut opponent) = (0, 0);
+            let mut stack = vec![(lr, lc)];
+            seen[lr][lc] = true;
+            while let Some((er, ec)) = stack.pop() {
+                for &(nr, nc) in orthogonal_neighbors(er, ec, size).as_slice() {
+                    match cells[nr][nc] {
+                        Cell::Empty if !seen[nr][nc] => {
+                            seen[nr][nc] = true;
+                            stack.push((nr, nc));
+                        }
+                        Cell::Empty => {}
+                        _ if border[nr][nc] => {}
+                        stone => {
+                            border[nr][nc] = true;
+                            if stone == color {
+                                own += 1;
+                            } else {
+                                opponent += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            regions.push((own, opponent));
+        }
+    }
+    regions
+}
+
+/// Bouzy 5/21 influence: positive for black, negative for white.
+fn bouzy(cells: &[[Cell; 19]; 19], size: usize) -> Influence {
+    let mut map = [[0; 19]; 19];
+    for row in 0..size {
+        for col in 0..size {
+            map[row][col] = match cells[row][col] {
+                Cell::Black => STONE_INFLUENCE,
+                Cell::White => -STONE_INFLUENCE,
+                Cell::Empty => 0,
+            };
+        }
+    }
+    for _ in 0..DILATIONS {
+        dilate(&mut map, size);
+    }
+    for _ in 0..EROSIONS {
+        erode(&mut map, size);
+    }
+    map
+}
+
+/// Grow each point by the number of same-signed neighbours, unless it
+/// touches a point of the opposite sign.
+fn dilate(map: &mut Influence, size: usize) {
+    let old = *map;
+    for row in 0..size {
+        for col in 0..size {
+            let v = old[row][col];
+            let nbrs = orthogonal_neighbors(row, col, size);
+            let values = nbrs.as_slice().iter().map(|&(r, c)| old[r][c]);
+            if v >= 0 && values.clone().all(|n| n >= 0) {
+                map[row][col] += values.filter(|&n| n > 0).count() as i32;
+            } else if v <= 0 && values.clone().all(|n| n <= 0) {
+                map[row][col] -= values.filter(|&n| n < 0).count() as i32;
+            }
+        }
+    }
+}
+
+/// Shrink each point towards zero by the number of neighbours that are zero
+/// or of the opposite sign.
+fn erode(map: &mut Influence, size: usize) {
+    let old = *map;
+    for row in 0..size {
+        for col in 0..size {
+            let v = old[row][col];
+            let nbrs = orthogonal_neighbors(row, col, size);
+            let values = nbrs.as_slice().iter().map(|&(r, c)| old[r][c]);
+            if v > 0 {
+                map[row][col] = (v - values.filter(|&n| n <= 0).count() as i32).max(0);
+            } else if v < 0 {
+                map[row][col] = (v + values.filter(|&n| n >= 0).count() as i32).min(0);
+            }
+        }
+    }
+}
+
+/// Give neutral areas bordered by only one side's influence to that side.
+fn fill_enclosed(map: &mut Influence, size: usize) {
+    let mut seen = [[false; 19]; 19];
+    for row in 0..size {
+        for col in 0..size {
+            if map[row][col] != 0 || seen[row][col] {
+                continue;
+            }
+            let (mut black, mut white) = (false, false);
+            let mut area = Vec::new();
+            let mut stack = vec![(row, col)];
+            seen[row][col] = true;
+            while let Some((r, c)) = stack.pop() {
+                area.push((r, c));
+                for &(nr, nc) in orthogonal_neighbors(r, c, size).as_slice() {
+                    match map[nr][nc] {
+                        0 if !seen[nr][nc] => {
+                            seen[nr][nc] = true;
+                            stack.push((nr, nc));
+                        }
+                        0 => {}
+                        v if v > 0 => black = true,
+                        _ => white = true,
+                    }
+                }
+            }
+            let fill = match (black, white) {
+                (true, false) => 1,
+                (false, true) => -1,
+                _ => continue,
+            };
+            for (r, c) in area {
+                map[r][c] = fill;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{Rules, parse_sgf};
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.roots[0])
+    }
+
+    #[test]
+    fn walls_split_the_board_and_invaders_die() {
+        let mut b = board("(;KM[6.5])");
+        for row in 0..19 {
+            b.cells[row][9] = Cell::Black;
+            b.cells[row][10] = Cell::White;
+        }
+        b.cells[3][3] = Cell::White;
+        b.cells[3][4] = Cell::White;
+        for &(r, c) in &[(2, 3), (2, 4), (4, 3), (4, 4), (3, 6)] {
+            b.cells[r][c] = Cell::Black;
+        }
+
+        let estimate = b.estimate();
+        assert_eq!(estimate.dead, vec![(3, 3), (3, 4)]);
+        assert_eq!(estimate.owner(0, 0), Cell::Black);
+        assert_eq!(estimate.owner(3, 3), Cell::Black);
+        assert_eq!(estimate.owner(18, 18), Cell::White);
+        assert!(estimate.ownership(0, 0) > 0.0 && estimate.ownership(0, 18) < 0.0);
+        // Territory: 166 points including the two dead stones, plus them as
+        // prisoners.
+        assert_eq!(estimate.black, 168.0);
+        assert_eq!(estimate.white, 152.0 + 6.5);
+        assert_eq!(estimate.result(), "B+9.5");
+
+        b.rules = Rules::Chinese;
+        assert_eq!(b.estimate().result(), "B+12.5");
+    }
+
+    #[test]
+    fn opening_stones_are_not_dead() {
+        let tree = parse_sgf("(;GM[1]KM[6.5];B[pd];W[dd];B[pp])").unwrap();
+        let last = tree.iter_mainline(tree.roots[0]).last().unwrap().0;
+        let estimate = Board::from_tree(&tree, last).estimate();
+        assert!(estimate.dead.is_empty());
+        assert_eq!(estimate.owner(3, 3), Cell::White);
+        assert!(estimate.margin().abs() < 20.0, "{}", estimate.result());
+    }
+
+    #[test]
+    fn living_group_with_two_eyes_survives() {
+        let mut b = board("(;)");
+        for row in 0..19 {
+            b.cells[row][9] = Cell::Black;
+            b.cells[row][10] = Cell::White;
+        }
+        // A white group with eyes at (0,1) and (0,3) in black's area.
+        for &(r, c) in &[
+            (0, 0),
+            (0, 2),
+            (0, 4),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+        ] {
+            b.cells[r][c] = Cell::White;
+        }
+        let estimate = b.estimate();
+        assert!(estimate.dead.is_empty());
+        assert_eq!(estimate.owner(0, 1), Cell::White);
+        let text = estimate.to_string();
+        assert_eq!(text.lines().count(), 19);
+        assert!(text.starts_with("O - O - O"));
+    }
+}
@@ -0,0 +1,220 @@
+use crate::sgf::{Board, Cell, ChainMap, GameTree, NodeId, SGFProperty, node::GoCoord};
+
+/// Give up reading after this many moves and treat the ladder as broken.
+const MAX_PLIES: usize = 400;
+
+/// The outcome of reading a ladder; see [`Board::read_ladder`].
+#[derive(Debug, Clone)]
+pub struct Ladder {
+    /// `true` if the attacker captures the chain whatever it does.
+    pub captured: bool,
+    /// The main line as `B`/`W` move properties.  When the ladder works it
+    /// ends with the capture, or with the chain in atari and unable to
+    /// extend; otherwise it shows how the chain escapes.
+    pub moves: Vec<SGFProperty>,
+}
+
+impl Ladder {
+    /// Add [`Ladder::moves`] below `parent` as a new variation, one node per
+    /// move, and return the last node (or `parent` if there are no moves).
+    pub fn insert(&self, tree: &mut GameTree, parent: NodeId) -> NodeId {
+        self.moves
+            .iter()
+            .fold(parent, |id, prop| tree.add_node(id, vec![prop.clone()]))
+    }
+}
+
+impl Board {
+    /// Read out the ladder against the chain at `(row, col)` with `to_play`
+    /// moving first.
+    ///
+    /// The attacker only ever plays on the chain's liberties, giving atari;
+    /// the chain may extend or capture an adjacent stone that is itself in
+    /// atari.  The chain escapes once it reaches three liberties.  Superko
+    /// is not considered.  Returns `None` if the point is empty or the
+    /// chain has more than two liberties.
+    pub fn read_ladder(&self, row: usize, col: usize, to_play: Cell) -> Option<Ladder> {
+        let prey = self.cells[row][col];
+        if prey == Cell::Empty || to_play == Cell::Empty {
+            return None;
+        }
+        let reader = Reader { row, col, prey };
+        let start = Position::new(self.scratch());
+        if reader.liberties(&start).len() > 2 {
+            return None;
+        }
+        let (captured, moves) = if to_play == prey {
+            reader.defend(&start, 0)
+        } else {
+            reader.attack(&start, 0)
+        };
+        Some(Ladder { captured, moves })
+    }
+}
+
+/// A position during the search: a board without superko history, so
+/// that each move copies only the stones, and its chains.
+struct Position {
+    board: Board,
+    chains: ChainMap,
+}
+
+impl Position {
+    fn new(board: Board) -> Self {
+        let chains = board.chains();
+        Position { board, chains }
+    }
+
+    /// The position after `color` plays at `(row, col)`, or `None` if the
+    /// move is illegal.
+    fn play(&self, color: Cell, (row, col): (usize, usize)) -> Option<Position> {
+        let mut board = self.board.scratch();
+        board.play(color, GoCoord::from_colrow(col, row)).ok()?;
+        Some(Position::new(board))
+    }
+}
+
+/// Recursive ladder search for the chain containing `(row, col)`.
+struct Reader {
+    row: usize,
+    col: usize,
+    prey: Cell,
+}
+
+impl Reader {
+    fn liberties(&self, position: &Position) -> Vec<(usize, usize)> {
+        let map = &position.chains;
+        map.chain_at(self.row, self.col)
+            .map(|id| map.chain(id).liberties.clone())
+            .unwrap_or_default()
+    }
+
+    fn prop(color: Cell, (row, col): (usize, usize)) -> SGFProperty {
+        let coord = GoCoord::from_colrow(col, row);
+        match color {
+            Cell::White => SGFProperty::W(coord),
+            _ => SGFProperty::B(coord),
+        }
+    }
+
+    /// Attacker to move.  Returns whether the chain is captured and the line
+    /// that shows it.
+    fn attack(&self, position: &Position, depth: usize) -> (bool, Vec<SGFProperty>) {
+        if position.board.cells[self.row][self.col] != self.prey {
+            return (true, Vec::new());
+        }
+        let attacker = self.prey.opposite();
+        let liberties = self.liberties(position);
+        if depth >= MAX_PLIES || liberties.len() > 2 {
+            return (false, Vec::new());
+        }
+
+        let mut refutation = None;
+        for &point in &liberties {
+            let Some(next) = position.play(attacker, point) else {
+                continue;
+            };
+            let mut line = vec![Self::prop(attacker, point)];
+            if liberties.len() == 1 {
+                return (true, line);
+            }
+            let (captured, rest) = self.defend(&next, depth + 1);
+            line.extend(rest);
+            if captured {
+                return (true, line);
+            }
+            refutation.get_or_insert(line);
+        }
+        (false, refutation.unwrap_or_default())
+    }
+
+    /// Chain to move, in atari.  Tries extending and capturing adjacent
+    /// stones in atari; the chain is lost only if every try fails.
+    fn defend(&self, position: &Position, depth: usize) -> (bool, Vec<SGFProperty>) {
+        let liberties = self.liberties(position);
+        if liberties.len() != 1 {
+            return (false, Vec::new());
+        }
+        let map = &position.chains;
+        let Some(id) = map.chain_at(self.row, self.col) else {
+            return (true, Vec::new());
+        };
+        let mut tries = liberties;
+        for &enemy in &map.chain(id).enemies {
+            let enemy = map.chain(enemy);
+            if enemy.in_atari() && !tries.contains(&enemy.liberties[0]) {
+                tries.push(enemy.liberties[0]);
+            }
+        }
+
+        let mut longest: Option<Vec<SGFProperty>> = None;
+        for point in tries {
+            let Some(next) = position.play(self.prey, point) else {
+                continue;
+            };
+            let mut line = vec![Self::prop(self.prey, point)];
+            let (captured, rest) = self.attack(&next, depth + 1);
+            line.extend(rest);
+            if !captured {
+                return (false, line);
+            }
+            if longest.as_ref().is_none_or(|l| line.len() > l.len()) {
+                longest = Some(line);
+            }
+        }
+        (true, longest.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    // White dd with two liberties; black to move chases it diagonally to
+    // the lower right corner.
+    const LADDER: &str = "(;AB[cd][dc][ec]AW[dd]";
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.roots[0])
+    }
+
+    #[test]
+    fn ladder_captures_on_empty_board() {
+        let b = board(&format!("{LADDER})"));
+        let ladder = b.read_ladder(3, 3, Cell::Black).unwrap();
+        assert!(ladder.captured);
+        assert_eq!(ladder.moves.len(), 59);
+        assert!(ladder.moves.iter().step_by(2).all(|p| p.tag() == "B"));
+        assert!(
+            ladder
+                .moves
+                .iter()
+                .skip(1)
+                .step_by(2)
+                .all(|p| p.tag() == "W")
+        );
+
+        let mut tree = parse_sgf(&format!("{LADDER})")).unwrap();
+        let root = tree.roots[0];
+        let end = ladder.insert(&mut tree, root);
+        let after = Board::from_tree(&tree, end);
+        let map = after.chains();
+        let prey = map.chain(map.chain_at(3, 3).unwrap());
+        assert!(prey.in_atari());
+        assert!(after.is_suicide(prey.liberties[0].0, prey.liberties[0].1, Cell::White));
+    }
+
+    #[test]
+    fn ladder_breaker_lets_chain_escape() {
+        let b = board(&format!("{LADDER}AW[pp])"));
+        let ladder = b.read_ladder(3, 3, Cell::Black).unwrap();
+        assert!(!ladder.captured);
+        assert!(!ladder.moves.is_empty());
+
+        // Not a ladder: too many liberties, or no stone at all.
+        assert!(b.read_ladder(15, 15, Cell::Black).is_none());
+        assert!(b.read_ladder(10, 10, Cell::Black).is_none());
+    }
+}
@@ -0,0 +1,275 @@
+use crate::sgf::{Board, Cell, ChainId, ChainMap, orthogonal_neighbors};
+
+/// How an [`EyeRegion`] is likely to serve its owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EyeKind {
+    /// A single empty point with enough of its diagonals safe to be an eye.
+    True,
+    /// A single empty point the opponent can spoil through its diagonals:
+    /// two or more opponent stones on the diagonals, or any on the edge.
+    False,
+    /// Several points, or opponent stones inside: an eye space that may make
+    /// one eye or more depending on its shape and on who plays first.
+    Space,
+}
+
+/// A maximal connected area holding no stones of `color` and bordered only
+/// by `color` and the board edge.  It may contain empty points and opponent
+/// stones.
+#[derive(Debug, Clone)]
+pub struct EyeRegion {
+    /// The enclosing colour.
+    pub color: Cell,
+    /// All points of the region, in row-major order.
+    pub points: Vec<(usize, usize)>,
+    /// Enclosing chains, in ascending order.
+    pub border: Vec<ChainId>,
+    /// Border chains for which every empty point of the region is a
+    /// liberty, so the region is "vital" in Benson's sense.
+    pub vital_to: Vec<ChainId>,
+    pub kind: EyeKind,
+}
+
+/// A connected area found by [`Board::areas`].
+pub(crate) struct Area {
+    /// All points of the area, in row-major order.
+    pub points: Vec<(usize, usize)>,
+    /// Chains next to the area, in ascending order.
+    pub border: Vec<ChainId>,
+}
+
+/// Which chains are alive however the opponent plays, by Benson's
+/// algorithm; see [`Board::unconditional_life`].
+#[derive(Debug, Clone)]
+pub struct Life {
+    chains: ChainMap,
+    alive: Vec<bool>,
+    /// Regions vital to a live chain whose border chains are all alive.
+    pub eyes: Vec<EyeRegion>,
+}
+
+impl Life {
+    /// The chains of the analysed position.
+    pub fn chains(&self) -> &ChainMap {
+        &self.chains
+    }
+
+    /// Unconditionally alive chains, in ascending order.
+    pub fn alive(&self) -> Vec<ChainId> {
+        (0..self.alive.len()).filter(|&id| self.alive[id]).collect()
+    }
+
+    /// Return `true` if the stone at `(row, col)` belongs to an
+    /// unconditionally alive chain.
+    pub fn is_alive(&self, row: usize, col: usize) -> bool {
+        self.chains
+            .chain_at(row, col)
+            .is_some_and(|id| self.alive[id])
+    }
+}
+
+impl Board {
+    /// Classify every area enclosed by `color`; see [`EyeRegion`].
+    pub fn eye_regions(&self, color: Cell) -> Vec<EyeRegion> {
+        self.regions_with(color, &self.chains())
+    }
+
+    /// Find the chains of both colours that cannot be captured even if
+    /// their owner passes every move (Benson's algorithm).
+    ///
+    /// Starting from all chains of a colour and all regions enclosed by it,
+    /// repeatedly drop chains with fewer than two vital regions and regions
+    /// bordered by a dropped chain.  What remains is alive.  Groups that
+    /// need to answer the opponent's moves to live, or that live in seki,
+    /// are not reported.
+    pub fn unconditional_life(&self) -> Life {
+        let chains = self.chains();
+        let mut alive = vec![false; chains.chains().len()];
+        let mut eyes = Vec::new();
+
+        for color in [Cell::Black, Cell::White] {
+            let regions = self.regions_with(color, &chains);
+            let mut live: Vec<bool> = chains.chains().iter().map(|c| c.color == color).collect();
+            let mut healthy = vec![true; regions.len()];
+            loop {
+                let mut changed = false;
+                for (id, chain_live) in live.iter_mut().enumerate() {
+                    if !*chain_live {
+                        continue;
+                    }
+                    let vital = regions
+                        .iter()
+                        .zip(&healthy)
+                        .filter(|(region, healthy)| **healthy && region.vital_to.contains(&id))
+                        .count();
+                    if vital < 2 {
+                        *chain_live = false;
+                        changed = true;
+                    }
+                }
+                for (region, healthy) in regions.iter().zip(healthy.iter_mut()) {
+                    if *healthy && region.border.iter().any(|&id| !live[id]) {
+                        *healthy = false;
+                        changed = true;
+                    }
+                }
+                if !changed {
+                    break;
+                }
+            }
+
+            for (id, &live) in live.iter().enumerate() {
+                alive[id] |= live;
+            }
+            eyes.extend(
+                regions
+                    .into_iter()
+                    .zip(healthy)
+                    .filter(|(region, healthy)| *healthy && !region.vital_to.is_empty())
+                    .map(|(region, _)| region),
+            );
+        }
+
+        Life {
+            chains,
+            alive,
+            eyes,
+        }
+    }
+
+    fn regions_with(&self, color: Cell, chains: &ChainMap) -> Vec<EyeRegion> {
+        self.areas(chains, |cell| cell != color)
+            .into_iter()
+            .map(|Area { points, border }| {
+                let vital_to = border
+                    .iter()
+                    .copied()
+                    .filter(|&id| {
+                        let liberties = &chains.chain(id).liberties;
+                        points
+                            .iter()
+                            .filter(|&&(r, c)| self.cells[r][c] == Cell::Empty)
+                            .all(|p| liberties.contains(p))
+                    })
+                    .collect();
+                let kind = match points[..] {
+                    [(r, c)] if self.cells[r][c] == Cell::Empty => {
+                        self.single_eye_kind(r, c, color)
+                    }
+                    _ => EyeKind::Space,
+                };
+                EyeRegion {
+                    color,
+                    points,
+                    border,
+                    vital_to,
+                    kind,
+                }
+            })
+            .collect()
+    }
+
+    /// Every maximal connected area of points whose occupant satisfies
+    /// `inside`, with the chains bordering it, in row-major order of each
+    /// area's first point.
+    pub(crate) fn areas(&self, chains: &ChainMap, inside: impl Fn(Cell) -> bool) -> Vec<Area> {
+        let size = self.size;
+        let mut seen = [[false; 19]; 19];
+        let mut areas = Vec::new();
+        for row in 0..size {
+            for col in 0..size {
+                if !inside(self.cells[row][col]) || seen[row][col] {
+                    continue;
+                }
+                let mut points = Vec::new();
+                let mut border = Vec::new();
+                let mut stack = vec![(row, col)];
+                seen[row][col] = true;
+                while let Some((r, c)) = stack.pop() {
+                    points.push((r, c));
+                    for &(nr, nc) in orthogonal_neighbors(r, c, size).as_slice() {
+                        if !inside(self.cells[nr][nc]) {
+                            let id = chains.chain_at(nr, nc).expect("stone belongs to a chain");
+                            if !border.contains(&id) {
+                                border.push(id);
+                            }
+                        } else if !seen[nr][nc] {
+                            seen[nr][nc] = true;
+                            stack.push((nr, nc));
+                        }
+                    }
+                }
+                points.sort_unstable();
+                border.sort_unstable();
+                areas.push(Area { points, border });
+            }
+        }
+        areas
+    }
+
+    /// Apply the diagonal rule to a one-point eye of `color`.
+    fn single_eye_kind(&self, row: usize, col: usize, color: Cell) -> EyeKind {
+        let size = self.size as isize;
+        let (mut diagonals, mut spoiled) = (0, 0);
+        for (dr, dc) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+            let (r, c) = (row as isize + dr, col as isize + dc);
+            if r < 0 || c < 0 || r >= size || c >= size {
+                continue;
+            }
+            diagonals += 1;
+            if self.cells[r as usize][c as usize] == color.opposite() {
+                spoiled += 1;
+            }
+        }
+        let limit = if diagonals < 4 { 1 } else { 2 };
+        if spoiled >= limit {
+            EyeKind::False
+        } else {
+            EyeKind::True
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.roots[0])
+    }
+
+    #[test]
+    fn two_eyes_are_unconditionally_alive() {
+        // Black corner group with eyes at aa and ca; a lone white stone and
+        // a one-eyed black group elsewhere.
+        let b = board("(;AB[ba][da][ab][bb][cb][db][ea][eb]AW[pp]AB[jj][ki][kk][lj]AW[kj])");
+        let life = b.unconditional_life();
+        assert!(life.is_alive(0, 1));
+        assert!(life.is_alive(1, 4));
+        assert!(!life.is_alive(15, 15));
+        assert!(!life.is_alive(9, 9));
+        assert_eq!(life.alive().len(), 1);
+        assert_eq!(life.eyes.len(), 2);
+        assert!(life.eyes.iter().all(|eye| eye.kind == EyeKind::True));
+    }
+
+    #[test]
+    fn single_point_eyes_are_classified() {
+        // Corner eye at aa spoiled by white bb; centre eye at jj with one
+        // white diagonal; centre eye at pp with two.
+        let b = board("(;AB[ba][ab][ji][ij][kj][jk][po][op][qp][pq]AW[bb][ii][oo][qq])");
+        let regions = b.eye_regions(Cell::Black);
+        let kind = |point| {
+            regions
+                .iter()
+                .find(|r| r.points == vec![point])
+                .map(|r| r.kind)
+        };
+        assert_eq!(kind((0, 0)), Some(EyeKind::False));
+        assert_eq!(kind((9, 9)), Some(EyeKind::True));
+        assert_eq!(kind((15, 15)), Some(EyeKind::False));
+        assert!(regions.iter().any(|r| r.kind == EyeKind::Space));
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::{Result, ensure};
+
+use crate::sgf::{GameTree, NodeId, SGFProperty, node::GoCoord};
+
+/// A property that both sides of a merge set to different values.
+///
+/// The merged tree keeps `ours`; `theirs` is reported so the caller can
+/// resolve it by hand.  Differing comments are never conflicts — they are
+/// concatenated instead.
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    /// Node in the merged tree that carries the conflicting property.
+    pub node: NodeId,
+    pub ours: SGFProperty,
+    pub theirs: SGFProperty,
+}
+
+/// Result of [`merge_trees`].
+#[derive(Debug)]
+pub struct MergeOutcome {
+    pub tree: GameTree,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Fold `theirs` into a copy of `ours`.
+///
+/// Game records are paired by index; records only present in `theirs` are
+/// appended as extra roots.  Within a pair, nodes that play the same move
+/// (or, for move-less nodes, carry the same setup stones in any order) are
+/// joined, so identical move sequences appear once and new lines become
+/// variations.  Nodes with neither a move nor setup are never joined.  When
+/// two nodes are joined, properties missing on our side are copied over,
+/// comments are concatenated, and any other differing value is kept from
+/// `ours` and reported as a [`MergeConflict`].
+///
+/// # Errors
+///
+/// Returns an error if a pair of game records does not start from the same
+/// position (different `SZ` or different setup stones on the root), since
+/// their move sequences cannot be aligned.
+pub fn merge_trees(ours: &GameTree, theirs: &GameTree) -> Result<MergeOutcome> {
+    let mut tree = ours.clone();
+    let mut conflicts = Vec::new();
+
+    for (i, &their_root) in theirs.roots.iter().enumerate() {
+        match tree.roots.get(i).copied() {
+            Some(our_root) => {
+                ensure!(
+                    node_key(&tree, our_root) == node_key(theirs, their_root)
+                        && board_size(&tree, our_root) == board_size(theirs, their_root),
+                    "Game {} does not start from the same position in both trees",
+                    i + 1
+                );
+                let grafted = tree.graft_subtree(theirs, their_root, our_root);
+                tree.join_nodes(our_root, grafted, &mut conflicts);
+                tree.dedup_children(our_root, &mut conflicts);
+            }
+            None => {
+                let root = tree.add_root(theirs.node(their_root).properties.clone());
+                for &child in &theirs.node(their_root).children {
+                    tree.graft_subtree(theirs, child, root);
+                }
+            }
+        }
+    }
+
+    Ok(MergeOutcome { tree, conflicts })
+}
+
+impl GameTree {
+    /// Join sibling variations that start with the same move, recursively,
+    /// across every game record in the tree.
+    ///
+    /// Properties are combined as described in [`merge_trees`]; the returned
+    /// list holds the properties that could not be combined.
+    pub fn dedup_variations(&mut self) -> Vec<MergeConflict> {
+        let mut conflicts = Vec::new();
+        for root in self.roots.clone() {
+            self.dedup_children(root, &mut conflicts);
+        }
+        conflicts
+    }
+
+    /// Join duplicate children of `id`, then recurse into the survivors.
+    /// Children without a move or setup stones are never joined.
+    fn dedup_children(&mut self, id: NodeId, conflicts: &mut Vec<MergeConflict>) {
+        let mut i = 0;
+        while i < self.nodes[id].children.len() {
+            let keep = self.nodes[id].children[i];
+            let Some(key) = node_key(self, keep) else {
+                i += 1;
+                continue;
+            };
+            let mut j = i + 1;
+            while j < self.nodes[id].children.len() {
+                let other = self.nodes[id].children[j];
+                if node_key(self, other).as_ref() == Some(&key) {
+                    // `join_nodes` unlinks `other`, so `j` now points at the next sibling.
+                    self.join_nodes(keep, other, conflicts);
+                } else {
+                    j += 1;
+                }
+            }
+            i += 1;
+        }
+
+        for child in self.nodes[id].children.clone() {
+            self.dedup_children(child, conflicts);
+        }
+    }
+
+    /// Fold node `other` into `keep`: combine properties, re-parent the
+    /// children of `other` under `keep`, and unlink `other`.
+    fn join_nodes(&mut self, keep: NodeId, other: NodeId, conflicts: &mut Vec<MergeConflict>) {
+        let incoming = std::mem::take(&mut self.nodes[other].properties);
+        for prop in incoming {
+            let existing = self.nodes[keep]
+                .properties
+                .iter_mut()
+                .find(|p| p.tag() == prop.tag());
+            match (existing, prop) {
+                (None, prop) => self.nodes[keep].properties.push(prop),
+                (Some(SGFProperty::C(ours)), SGFProperty::C(theirs)) => {
+                    if *ours != theirs {
+                        ours.push_str("\n\n");
+                        ours.push_str(&theirs);
+                    }
+                }
+                (Some(ours), theirs) => {
+                    if !same_value(ours, &theirs) {
+                        conflicts.push(MergeConflict {
+                            node: keep,
+                            ours: ours.clone(),
+                            theirs,
+                        });
+                    }
+                }
+            }
+        }
+
+        let children = std::mem::take(&mut self.nodes[other].children);
+        for &child in &children {
+            self.nodes[child].parent = Some(keep);
+        }
+        self.nodes[keep].children.extend(children);
+        self.remove_subtree(other);
+    }
+}
+
+/// Identity used to decide whether two nodes are "the same": the move if the
+/// node has one, otherwise its setup stones regardless of the order they are
+/// listed in.  `None` for a node with neither.
+pub(crate) fn node_key(tree: &GameTree, id: NodeId) -> Option<String> {
+    let props = &tree.node(id).properties;
+    if let Some(mv) = props
+        .iter()
+        .find(|p| matches!(p, SGFProperty::B(_) | SGFProperty::W(_)))
+    {
+        return Some(mv.to_string());
+    }
+    let black = sorted_points(props.iter().flat_map(|p| match p {
+        SGFProperty::AB(coords) => coords.as_slice(),
+        _ => &[],
+    }));
+    let white = sorted_points(props.iter().flat_map(|p| match p {
+        SGFProperty::AW(coords) => coords.as_slice(),
+        _ => &[],
+    }));
+    if black.is_empty() && white.is_empty() {
+        return None;
+    }
+    Some(format!("AB[{}]AW[{}]", black.join("]["), white.join("][")))
+}
+
+/// Whether two properties with the same tag carry the same value, ignoring
+/// the order of setup points.
+fn same_value(a: &SGFProperty, b: &SGFProperty) -> bool {
+    match (a, b) {
+        (SGFProperty::AB(x), SGFProperty::AB(y)) | (SGFProperty::AW(x), SGFProperty::AW(y)) => {
+            sorted_points(x) == sorted_points(y)
+        }
+        _ => a.to_string() == b.to_string(),
+    }
+}
+
+/// Points as sorted, distinct SGF coordinates.
+fn sorted_points<'a>(coords: impl IntoIterator<Item = &'a GoCoord>) -> Vec<String> {
+    let mut points: Vec<String> = coords.into_iter().map(|c| c.to_string()).collect();
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+fn board_size(tree: &GameTree, root: NodeId) -> u8 {
+    tree.node(root)
+        .properties
+        .iter()
+        .find_map(|p| match p {
+            SGFProperty::SZ(n) => Some(*n),
+            _ => None,
+        })
+        .unwrap_or(19)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{parse_sgf, write_sgf};
+
+    #[test]
+    fn shared_prefix_is_joined_and_new_lines_become_variations() {
+        let ours = parse_sgf("(;GM[1]SZ[19];B[pd];W[dd];B[pp])").unwrap();
+        let theirs = parse_sgf("(;GM[1]SZ[19];B[pd];W[dd];B[dp])").unwrap();
+        let merged = merge_trees(&ours, &theirs).unwrap();
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            write_sgf(&merged.tree),
+            "(;GM[1]SZ[19];B[pd];W[dd](;B[pp])(;B[dp]))"
+        );
+    }
+
+    #[test]
+    fn comments_are_concatenated_and_game_info_conflicts_reported() {
+        let ours = parse_sgf("(;GM[1]PB[Alice]RE[B+R];B[pd]C[good])").unwrap();
+        let theirs = parse_sgf("(;GM[1]PB[Alice]RE[W+2.5]PW[Bob];B[pd]C[slow])").unwrap();
+        let merged = merge_trees(&ours, &theirs).unwrap();
+        let tree = &merged.tree;
+        let root = tree.roots[0];
+
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].node, root);
+        assert_eq!(merged.conflicts[0].theirs.to_string(), "RE[W+2.5]");
+        assert!(
+            tree.node(root)
+                .properties
+                .iter()
+                .any(|p| p.to_string() == "PW[Bob]")
+        );
+
+        let mv = tree.node(root).children[0];
+        assert!(
+            tree.node(mv)
+                .properties
+                .iter()
+                .any(|p| p.to_string() == "C[good\n\nslow]")
+        );
+    }
+
+    #[test]
+    fn different_setup_is_rejected() {
+        let ours = parse_sgf("(;GM[1]AB[dd];W[pp])").unwrap();
+        let theirs = parse_sgf("(;GM[1];B[dd];W[pp])").unwrap();
+        assert!(merge_trees(&ours, &theirs).is_err());
+    }
+
+    #[test]
+    fn dedup_variations_joins_identical_siblings() {
+        let mut tree = parse_sgf("(;GM[1](;B[pd];W[dd])(;B[pd];W[dp])(;B[pd];W[dd]))").unwrap();
+        let conflicts = tree.dedup_variations();
+        assert!(conflicts.is_empty());
+        assert_eq!(write_sgf(&tree), "(;GM[1];B[pd](;W[dd])(;W[dp]))");
+    }
+
+    #[test]
+    fn setup_order_does_not_matter_and_empty_nodes_stay_apart() {
+        let ours = parse_sgf("(;GM[1]AB[dd][pp]AW[dp];W[pd])").unwrap();
+        let theirs = parse_sgf("(;GM[1]AW[dp]AB[pp][dd];W[pd]C[ok])").unwrap();
+        let merged = merge_trees(&ours, &theirs).unwrap();
+        assert!(merged.conflicts.is_empty(), "{merged:?}");
+        assert_eq!(merged.tree.iter_subtree(merged.tree.roots[0]).count(), 2);
+
+        let mut tree =
+            parse_sgf("(;GM[1](;C[first])(;C[second])(;AB[aa]AW[bb])(;AW[bb]AB[aa]))").unwrap();
+        tree.dedup_variations();
+        assert_eq!(
+            write_sgf(&tree),
+            "(;GM[1](;C[first])(;C[second])(;AB[aa]AW[bb]))"
+        );
+    }
+}
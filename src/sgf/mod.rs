@@ -10,16 +10,78 @@
 //! - [`SGFProperty`] — a single SGF property (e.g. `B[dd]`, `KM[6.5]`).
 //! - [`GameTree`] — arena-allocated tree of [`TreeNode`]s indexed by [`NodeId`].
 //! - [`Board`] — a Go board position derived from a tree path via [`Board::from_tree`].
+//! - [`BoardState`] / [`BoardCursor`] — a board that steps forwards and back
+//!   along the tree without replaying from the root.
 //! - [`node::GoCoord`] — a pair of SGF board coordinates (e.g. `dd`).
+//! - [`NodePath`] — a stable, printable node address (e.g. `1:0.0.2.0`).
+//!
+//! [`merge_trees`] folds two annotated copies of the same game into one,
+//! [`diff_trees`] reports what changed between two versions of a record,
+//! [`Transpositions`] finds variations that reach the same position, and
+//! [`Query`] searches a collection for nodes by move, comment or property.
+//! [`Rules`] (from the `RU` property) decides suicide and superko handling
+//! in the simulator and in [`Board::is_legal`]; [`Board::score`] counts a
+//! finished position under those rules, and [`Board::estimate`] guesses
+//! ownership for an unfinished one.  [`Board::chains`] lists every chain with
+//! its liberties and neighbouring enemy chains, and [`Board::read_ladder`]
+//! reads out whether a ladder works.  [`Board::unconditional_life`] finds
+//! groups that live whatever the opponent does (Benson's algorithm) and
+//! [`Board::eye_regions`] classifies the areas a colour encloses.
+//! [`Symmetry`] rotates or mirrors a [`node::GoCoord`], a [`Board`] or a
+//! whole [`GameTree`], and [`Board::canonical`] picks one orientation for
+//! hashing.  [`Pattern`] finds a local shape on a board, or across every
+//! game of a collection, in any orientation and with either colours.
+//! [`GameSetup`] builds the root of a new game, including [`Handicap`]
+//! stones on the traditional [`fixed_handicap`] points.
 
+pub mod bitboard;
 pub mod board;
+pub mod chains;
+pub mod diff;
+pub mod estimate;
+pub mod ladder;
+pub mod life;
+pub mod merge;
 pub mod node;
 mod parser;
+pub mod path;
+pub mod pattern;
+pub mod query;
+pub mod rules;
+pub mod scoring;
 mod serializer;
+pub mod setup;
+pub mod state;
+pub mod symmetry;
+pub mod transform;
+pub mod transposition;
 pub mod tree;
+mod zobrist;
 
-pub use board::{Board, Cell, Neighbors, count_liberties, find_group, orthogonal_neighbors};
+pub use bitboard::{BitBoard, Bits};
+pub use board::{
+    Board, Cell, IllegalMove, MoveOutcome, Neighbors, SuperkoRule, count_liberties, find_group,
+    find_superko_violations, orthogonal_neighbors,
+};
+pub use chains::{Chain, ChainId, ChainMap};
+pub use diff::{TreeDiff, diff_trees};
+pub use estimate::Estimate;
+pub use ladder::Ladder;
+pub use life::{EyeKind, EyeRegion, Life};
+pub use merge::{MergeConflict, MergeOutcome, merge_trees};
 pub use node::SGFProperty;
 pub use parser::parse_sgf;
+pub use path::NodePath;
+pub use pattern::{Pattern, PatternCell, PatternHit, PatternMatch};
+pub use query::Query;
+pub use rules::{Rules, Scoring};
+pub use scoring::Score;
 pub use serializer::write_sgf;
-pub use tree::{GameTree, MainlineIter, NodeId, SubtreeIter, TreeNode};
+pub use setup::{GameSetup, Handicap, fixed_handicap};
+pub use state::{BoardCursor, BoardState};
+pub use symmetry::Symmetry;
+pub use transposition::Transpositions;
+pub use tree::{
+    AncestorsIter, BreadthFirstIter, DepthIter, GameTree, LeavesIter, MainlineIter, NodeId,
+    PostOrderIter, SiblingsIter, SubtreeIter, TreeNode, VariationsIter,
+};
@@ -216,9 +216,15 @@ impl FromStr for GoCoord {
 }
 
 impl Komi {
-    fn new(n: f64) -> Self {
+    /// Komi of `n` points, rounded to the nearest half-point.
+    pub fn new(n: f64) -> Self {
         Komi((n * 2.0).round() as i16)
     }
+
+    /// The komi in points (e.g. `6.5`).
+    pub fn points(&self) -> f64 {
+        f64::from(self.0) / 2.0
+    }
 }
 
 impl Default for Komi {
@@ -306,6 +312,34 @@ impl Display for Charset {
     }
 }
 
+impl SGFProperty {
+    /// The SGF tag identifying this property (e.g. `"B"`, `"KM"`).  For
+    /// [`SGFProperty::Unknown`] this is the raw tag string.
+    pub fn tag(&self) -> &str {
+        match self {
+            Self::AP(_) => "AP",
+            Self::B(_) => "B",
+            Self::W(_) => "W",
+            Self::AB(_) => "AB",
+            Self::AW(_) => "AW",
+            Self::CA(_) => "CA",
+            Self::DT(_) => "DT",
+            Self::FF(_) => "FF",
+            Self::GM(_) => "GM",
+            Self::KM(_) => "KM",
+            Self::SZ(_) => "SZ",
+            Self::PB(_) => "PB",
+            Self::PW(_) => "PW",
+            Self::BR(_) => "BR",
+            Self::WR(_) => "WR",
+            Self::HA(_) => "HA",
+            Self::RE(_) => "RE",
+            Self::C(_) => "C",
+            Self::Unknown(k, _) => k.as_str(),
+        }
+    }
+}
+
 impl Display for SGFProperty {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
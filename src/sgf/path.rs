@@ -0,0 +1,137 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+
+use crate::sgf::{GameTree, NodeId};
+
+/// A stable address for a node: the index of its game record plus the child
+/// index taken at each step down from the root.
+///
+/// Unlike a [`NodeId`], a `NodePath` means the same thing in every tree parsed
+/// from the same file, so it can be written into bug reports, links or
+/// config.  All indices are 0-based.
+///
+/// The string form is `GAME:I.J.K…`, e.g. `1:0.0.2.0` is the second game
+/// record, first child, first child, third child, first child.  The root of a
+/// game record is written as just `GAME` (`GAME:` is accepted when parsing).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NodePath {
+    /// Index into [`GameTree::roots`].
+    pub game: usize,
+    /// Child index taken at each level below the root.
+    pub children: Vec<usize>,
+}
+
+impl NodePath {
+    /// Path to the root of game record `game`.
+    pub fn root(game: usize) -> Self {
+        Self {
+            game,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Display for NodePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.game)?;
+        for (i, idx) in self.children.iter().enumerate() {
+            let sep = if i == 0 { ':' } else { '.' };
+            write!(f, "{sep}{idx}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NodePath {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (game, rest) = s.split_once(':').unwrap_or((s, ""));
+        let game = game
+            .trim()
+            .parse()
+            .context(format!("Invalid node path {:?}: bad game index", s))?;
+        let children = if rest.is_empty() {
+            Vec::new()
+        } else {
+            rest.split('.')
+                .map(|part| part.trim().parse::<usize>())
+                .collect::<std::result::Result<_, _>>()
+                .context(format!("Invalid node path {:?}: bad child index", s))?
+        };
+        Ok(Self { game, children })
+    }
+}
+
+impl GameTree {
+    /// Compute the [`NodePath`] of `id`.
+    ///
+    /// Returns `None` if `id` is no longer reachable from any root (e.g. it
+    /// was unlinked by [`GameTree::remove_subtree`]).
+    pub fn node_path(&self, id: NodeId) -> Option<NodePath> {
+        let mut children = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            let idx = self.nodes[parent]
+                .children
+                .iter()
+                .position(|&c| c == current)?;
+            children.push(idx);
+            current = parent;
+        }
+        children.reverse();
+        let game = self.roots.iter().position(|&r| r == current)?;
+        Some(NodePath { game, children })
+    }
+
+    /// Resolve `path` to a [`NodeId`] in this tree, or `None` if any index is
+    /// out of range.
+    pub fn node_at(&self, path: &NodePath) -> Option<NodeId> {
+        let mut id = *self.roots.get(path.game)?;
+        for &idx in &path.children {
+            id = *self.nodes[id].children.get(idx)?;
+        }
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    #[test]
+    fn string_form_round_trips() {
+        for s in ["0", "1:0.0.2.0", "3:5"] {
+            assert_eq!(s.parse::<NodePath>().unwrap().to_string(), s);
+        }
+        assert_eq!("2:".parse::<NodePath>().unwrap(), NodePath::root(2));
+        assert!("x:1".parse::<NodePath>().is_err());
+        assert!("1:0..2".parse::<NodePath>().is_err());
+    }
+
+    #[test]
+    fn node_id_conversions() {
+        let tree = parse_sgf("(;GM[1])(;GM[1];B[dd](;W[pp])(;W[dp];B[pp]))").unwrap();
+        let path: NodePath = "1:0.1.0".parse().unwrap();
+        let id = tree.node_at(&path).unwrap();
+        assert_eq!(tree.node(id).properties[0].to_string(), "B[pp]");
+        assert_eq!(tree.node_path(id), Some(path));
+        assert_eq!(tree.node_path(tree.roots[0]), Some(NodePath::root(0)));
+        assert_eq!(tree.node_at(&"1:0.2".parse().unwrap()), None);
+        assert_eq!(tree.node_at(&"2".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn unlinked_node_has_no_path() {
+        let mut tree = parse_sgf("(;GM[1];B[dd];W[pp])").unwrap();
+        let b = tree.node(tree.roots[0]).children[0];
+        let w = tree.node(b).children[0];
+        tree.remove_subtree(b);
+        assert_eq!(tree.node_path(w), None);
+    }
+}
@@ -0,0 +1,378 @@
+use std::str::FromStr;
+
+use anyhow::{Result, bail, ensure};
+
+use crate::sgf::{Board, Cell, GameTree, NodeId, Symmetry};
+
+/// What a single point of a [`Pattern`] accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternCell {
+    /// Anything.  The whole pattern must still lie on the board.
+    Any,
+    Empty,
+    Black,
+    White,
+    /// A stone of either colour.
+    Stone,
+}
+
+impl PatternCell {
+    fn accepts(self, cell: Cell) -> bool {
+        match self {
+            PatternCell::Any => true,
+            PatternCell::Empty => cell == Cell::Empty,
+            PatternCell::Black => cell == Cell::Black,
+            PatternCell::White => cell == Cell::White,
+            PatternCell::Stone => cell != Cell::Empty,
+        }
+    }
+
+    fn reversed(self) -> PatternCell {
+        match self {
+            PatternCell::Black => PatternCell::White,
+            PatternCell::White => PatternCell::Black,
+            other => other,
+        }
+    }
+}
+
+/// A rectangular local shape to look for on a [`Board`], in any of the
+/// eight orientations and with either colours.
+///
+/// Patterns are written one row per line (or separated by `/`), with
+/// whitespace inside a row ignored: `X` black, `O` white, `.` or `,` empty,
+/// `*` a stone of either colour and `?` anything.
+///
+/// ```
+/// use tesuji::parse_sgf;
+/// use tesuji::sgf::{Board, Pattern, Symmetry};
+///
+/// // A black stone on the 3-3 point, pinned to the top-left corner.
+/// let pattern: Pattern = ". . ./. . ./. . X".parse().unwrap();
+/// let pattern = pattern.at(0, 0);
+///
+/// let tree = parse_sgf("(;AW[qc])").unwrap();
+/// let board = Board::from_tree(&tree, tree.roots[0]);
+/// let hits = pattern.matches(&board);
+/// assert_eq!(hits.len(), 1);
+/// assert!(hits[0].colors_swapped);
+/// assert_eq!(hits[0].symmetry, Symmetry::Rotate90);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    height: usize,
+    width: usize,
+    /// Row-major cells.
+    cells: Vec<PatternCell>,
+    pinned: Option<(usize, usize)>,
+}
+
+/// Where and how a [`Pattern`] was found on a board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PatternMatch {
+    /// The board shows the pattern under this symmetry...
+    pub symmetry: Symmetry,
+    /// ...with black and white exchanged.
+    pub colors_swapped: bool,
+    /// Top-left board point of the (transformed) pattern.
+    pub row: usize,
+    pub col: usize,
+}
+
+/// A [`PatternMatch`] found by [`Pattern::search`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternHit {
+    /// Index of the game in [`GameTree::roots`].
+    pub game: usize,
+    /// The node where the pattern appears.
+    pub node: NodeId,
+    pub matched: PatternMatch,
+}
+
+/// One orientation of a pattern, ready to test against a board.
+struct Variant {
+    symmetry: Symmetry,
+    colors_swapped: bool,
+    height: usize,
+    width: usize,
+    /// Cells other than [`PatternCell::Any`], as offsets from the top left.
+    cells: Vec<(usize, usize, PatternCell)>,
+    pinned: Option<(usize, usize)>,
+}
+
+impl Pattern {
+    /// A pattern from rows of cells, which must all be the same, non-zero
+    /// length.
+    pub fn new(rows: Vec<Vec<PatternCell>>) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        ensure!(height > 0 && width > 0, "pattern is empty");
+        ensure!(
+            rows.iter().all(|row| row.len() == width),
+            "pattern rows must all have the same length"
+        );
+        Ok(Self {
+            height,
+            width,
+            cells: rows.into_iter().flatten().collect(),
+            pinned: None,
+        })
+    }
+
+    /// Only match with the top-left cell on board point `(row, col)`, or on
+    /// the image of that point under a symmetry.  Pinning a pattern to
+    /// `(0, 0)` makes it a corner pattern that matches in all four corners.
+    pub fn at(mut self, row: usize, col: usize) -> Self {
+        self.pinned = Some((row, col));
+        self
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> PatternCell {
+        self.cells[row * self.width + col]
+    }
+
+    /// Every placement of the pattern on `board`, over all eight symmetries
+    /// and both colourings.  Orientations that give the same shape (for a
+    /// symmetric pattern) are only tried once, so each placement is reported
+    /// once.
+    pub fn matches(&self, board: &Board) -> Vec<PatternMatch> {
+        matches_with(&self.variants(board.size), board)
+    }
+
+    /// Replay every game of `tree` and report the nodes where the pattern
+    /// appears: each match found at a node but not at its parent.  A shape
+    /// that stays on the board is reported once, at the move that completes
+    /// it, rather than at every later node.
+    pub fn search(&self, tree: &GameTree) -> Vec<PatternHit> {
+        let mut hits = Vec::new();
+        for (game, &root) in tree.roots.iter().enumerate() {
+            let board = Board::from_tree(tree, root);
+            let variants = self.variants(board.size);
+            // Carry each node's board and matches down to its children
+            // instead of replaying from the root for every node.
+            let mut stack = vec![(root, board, Vec::new())];
+            while let Some((id, board, parent_matches)) = stack.pop() {
+                let found = matches_with(&variants, &board);
+                hits.extend(found.iter().filter(|m| !parent_matches.contains(*m)).map(
+                    |&matched| PatternHit {
+                        game,
+                        node: id,
+                        matched,
+                    },
+                ));
+                for &child in tree.node(id).children.iter().rev() {
+                    let mut next = board.clone();
+                    next.apply_node(tree.node(child));
+                    stack.push((child, next, found.clone()));
+                }
+            }
+        }
+        hits
+    }
+
+    /// The distinct orientations of the pattern on a `size`×`size` board.
+    fn variants(&self, size: usize) -> Vec<Variant> {
+        let mut variants: Vec<Variant> = Vec::new();
+        for colors_swapped in [false, true] {
+            for symmetry in Symmetry::ALL {
+                let Some(variant) = self.variant(symmetry, colors_swapped, size) else {
+                    continue;
+                };
+                let duplicate = variants.iter().any(|v| {
+                    v.height == variant.height
+                        && v.width == variant.width
+                        && v.pinned == variant.pinned
+                        && v.cells == variant.cells
+                });
+                if !duplicate {
+                    variants.push(variant);
+                }
+            }
+        }
+        variants
+    }
+
+    fn variant(&self, symmetry: Symmetry, colors_swapped: bool, size: usize) -> Option<Variant> {
+        if self.height > size || self.width > size {
+            return None;
+        }
+        // Transform inside the bounding square, then shift back to the top
+        // left of the transformed rectangle.
+        let side = self.height.max(self.width);
+        let (a, b) = (
+            symmetry.apply(0, 0, side),
+            symmetry.apply(self.height - 1, self.width - 1, side),
+        );
+        let (top, left) = (a.0.min(b.0), a.1.min(b.1));
+        let (height, width) = (a.0.abs_diff(b.0) + 1, a.1.abs_diff(b.1) + 1);
+
+        let mut cells: Vec<(usize, usize, PatternCell)> = Vec::new();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let cell = self.cell(row, col);
+                if cell == PatternCell::Any {
+                    continue;
+                }
+                let (r, c) = symmetry.apply(row, col, side);
+                let cell = if colors_swapped {
+                    cell.reversed()
+                } else {
+                    cell
+                };
+                cells.push((r - top, c - left, cell));
+            }
+        }
+        cells.sort_by_key(|&(r, c, _)| (r, c));
+
+        let pinned = match self.pinned {
+            None => None,
+            Some((row, col)) => {
+                if row + self.height > size || col + self.width > size {
+                    return None;
+                }
+                let a = symmetry.apply(row, col, size);
+                let b = symmetry.apply(row + self.height - 1, col + self.width - 1, size);
+                Some((a.0.min(b.0), a.1.min(b.1)))
+            }
+        };
+
+        Some(Variant {
+            symmetry,
+            colors_swapped,
+            height,
+            width,
+            cells,
+            pinned,
+        })
+    }
+}
+
+fn matches_with(variants: &[Variant], board: &Board) -> Vec<PatternMatch> {
+    let size = board.size;
+    let mut out = Vec::new();
+    for v in variants {
+        let origins: Vec<(usize, usize)> = match v.pinned {
+            Some(origin) => vec![origin],
+            None => (0..=size - v.height)
+                .flat_map(|row| (0..=size - v.width).map(move |col| (row, col)))
+                .collect(),
+        };
+        for (row, col) in origins {
+            if v.cells
+                .iter()
+                .all(|&(r, c, cell)| cell.accepts(board.cells[row + r][col + c]))
+            {
+                out.push(PatternMatch {
+                    symmetry: v.symmetry,
+                    colors_swapped: v.colors_swapped,
+                    row,
+                    col,
+                });
+            }
+        }
+    }
+    out
+}
+
+impl FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut rows = Vec::new();
+        for line in s.split(['\n', '/']) {
+            let row = line
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(|c| {
+                    Ok(match c {
+                        'X' => PatternCell::Black,
+                        'O' => PatternCell::White,
+                        '.' | ',' => PatternCell::Empty,
+                        '*' => PatternCell::Stone,
+                        '?' => PatternCell::Any,
+                        _ => bail!("unknown pattern character '{c}'"),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if !row.is_empty() {
+                rows.push(row);
+            }
+        }
+        Pattern::new(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.iter_mainline(tree.roots[0]).last().unwrap().0)
+    }
+
+    #[test]
+    fn matches_every_orientation_and_colouring() {
+        let pattern: Pattern = "X O\n. *".parse().unwrap();
+        let b = board("(;AB[dd]AW[ed][ee])");
+        let found = pattern.matches(&b);
+        assert_eq!(
+            found,
+            vec![PatternMatch {
+                symmetry: Symmetry::Identity,
+                colors_swapped: false,
+                row: 3,
+                col: 3,
+            }]
+        );
+        for symmetry in Symmetry::ALL {
+            let found = pattern.matches(&b.transformed(symmetry));
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].symmetry, symmetry);
+        }
+        let reversed = pattern.matches(&board("(;AW[dd]AB[ed][ee])"));
+        assert_eq!(reversed.len(), 1);
+        assert!(reversed[0].colors_swapped);
+        assert!("X?\nQ".parse::<Pattern>().is_err());
+        assert!("XO\nX".parse::<Pattern>().is_err());
+    }
+
+    #[test]
+    fn search_reports_where_the_shape_appears() {
+        let tree = parse_sgf("(;GM[1];B[pd];W[dd])(;GM[1];B[cc];W[dc];B[cd];W[qq])").unwrap();
+        let pattern: Pattern = "X O\nX ?".parse().unwrap();
+        let hits = pattern.search(&tree);
+        assert_eq!(hits.len(), 1);
+        let third = tree.iter_mainline(tree.roots[1]).nth(3).unwrap().0;
+        assert_eq!(hits[0].game, 1);
+        assert_eq!(hits[0].node, third);
+        assert_eq!((hits[0].matched.row, hits[0].matched.col), (2, 2));
+
+        // Pinned to the corner, the same shape is not found in mid-board.
+        assert!(pattern.clone().at(0, 0).search(&tree).is_empty());
+    }
+
+    #[test]
+    fn small_boards_have_their_own_corners() {
+        let corner: Pattern = ". . ./. . ./. . X".parse().unwrap();
+        let corner = corner.at(0, 0);
+        let b = board("(;SZ[9];B[gc])");
+        let hits = corner.matches(&b);
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].row, hits[0].col), (0, 6));
+        assert!(!hits[0].colors_swapped);
+
+        // Empty points beyond the edge of a small board never match.
+        let empty: Pattern = "...\n...\n...".parse().unwrap();
+        assert_eq!(empty.matches(&board("(;SZ[9])")).len(), 49);
+    }
+}
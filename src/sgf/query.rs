@@ -0,0 +1,294 @@
+use std::{
+    collections::HashSet,
+    ops::{Bound, RangeBounds},
+};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::sgf::{
+    Cell, GameTree, NodeId, SGFProperty, TreeNode, board::move_number_property, node::GoCoord,
+};
+
+/// A single condition a node must satisfy.
+enum Predicate {
+    HasProperty(String),
+    PropertyValue(String, String),
+    Move {
+        color: Option<Cell>,
+        coord: Option<GoCoord>,
+    },
+    CommentContains(String),
+    CommentMatches(Regex),
+    Depth(Bound<usize>, Bound<usize>),
+    MoveNumber(Bound<usize>, Bound<usize>),
+}
+
+/// Builder for searching a [`GameTree`] for nodes that match every added
+/// condition.
+///
+/// ```
+/// use tesuji::parse_sgf;
+/// use tesuji::sgf::{Cell, Query};
+///
+/// let tree = parse_sgf("(;GM[1];B[pd]C[a joseki];W[dd];B[pp]C[joseki again])").unwrap();
+/// let hits = Query::new()
+///     .comment_contains("joseki")
+///     .player(Cell::Black)
+///     .move_number(2..)
+///     .run(&tree);
+/// assert_eq!(hits.len(), 1);
+/// ```
+///
+/// Depth counts nodes below the game root (the root has depth 0); move
+/// number counts `B`/`W` moves from the root up to and including the node,
+/// restarting from a move node's `MN`, like
+/// [`Board::move_number`](crate::sgf::Board::move_number).
+#[derive(Default)]
+pub struct Query {
+    predicates: Vec<Predicate>,
+}
+
+impl Query {
+    /// An empty query, which matches every node.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match nodes carrying a property with the given SGF tag (e.g. `"BM"`).
+    pub fn has_property(mut self, tag: &str) -> Self {
+        self.predicates
+            .push(Predicate::HasProperty(tag.to_string()));
+        self
+    }
+
+    /// Match nodes whose `tag` property has `value` among its values, compared
+    /// as raw SGF text (e.g. `property_value("PB", "Honinbo Shusaku")`).
+    pub fn property_value(mut self, tag: &str, value: &str) -> Self {
+        self.predicates
+            .push(Predicate::PropertyValue(tag.to_string(), value.to_string()));
+        self
+    }
+
+    /// Match moves played at `coord`, by either colour.
+    pub fn move_at(mut self, coord: GoCoord) -> Self {
+        self.predicates.push(Predicate::Move {
+            color: None,
+            coord: Some(coord),
+        });
+        self
+    }
+
+    /// Match moves (including passes) played by `color`.
+    pub fn player(mut self, color: Cell) -> Self {
+        self.predicates.push(Predicate::Move {
+            color: Some(color),
+            coord: None,
+        });
+        self
+    }
+
+    /// Match nodes whose comment contains `text`.
+    pub fn comment_contains(mut self, text: &str) -> Self {
+        self.predicates
+            .push(Predicate::CommentContains(text.to_string()));
+        self
+    }
+
+    /// Match nodes whose comment matches the regular expression `pattern`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` is not a valid regular expression.
+    pub fn comment_matches(mut self, pattern: &str) -> Result<Self> {
+        let re = Regex::new(pattern).context(format!("Invalid comment pattern {:?}", pattern))?;
+        self.predicates.push(Predicate::CommentMatches(re));
+        Ok(self)
+    }
+
+    /// Match nodes whose depth below the game root lies in `range`.
+    pub fn depth(mut self, range: impl RangeBounds<usize>) -> Self {
+        self.predicates.push(Predicate::Depth(
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+        ));
+        self
+    }
+
+    /// Match nodes whose move number lies in `range`.
+    pub fn move_number(mut self, range: impl RangeBounds<usize>) -> Self {
+        self.predicates.push(Predicate::MoveNumber(
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+        ));
+        self
+    }
+
+    /// Every matching node in every game record of `tree`, in DFS pre-order.
+    pub fn run(&self, tree: &GameTree) -> Vec<NodeId> {
+        let mut hits = Vec::new();
+        for &root in &tree.roots {
+            self.collect(tree, root, &mut hits);
+        }
+        hits
+    }
+
+    /// The first match after `after` in the order of [`Query::run`], wrapping
+    /// around to the start.  May return `after` itself if it is the only
+    /// match.
+    pub fn find_next(&self, tree: &GameTree, after: NodeId) -> Option<NodeId> {
+        Self::next_hit(tree, &self.run(tree), after)
+    }
+
+    /// Like [`Query::find_next`], but picks from `hits` already returned by
+    /// [`Query::run`] on `tree` instead of running the query again.
+    pub fn next_hit(tree: &GameTree, hits: &[NodeId], after: NodeId) -> Option<NodeId> {
+        let hits: HashSet<NodeId> = hits.iter().copied().collect();
+        let order: Vec<NodeId> = tree
+            .roots
+            .iter()
+            .flat_map(|&root| tree.iter_subtree(root).map(|(id, _)| id))
+            .collect();
+        let pos = order.iter().position(|&id| id == after)?;
+        order[pos + 1..]
+            .iter()
+            .chain(&order[..=pos])
+            .find(|id| hits.contains(id))
+            .copied()
+    }
+
+    fn collect(&self, tree: &GameTree, root: NodeId, hits: &mut Vec<NodeId>) {
+        // (node, depth, move number including the node)
+        let mut stack = vec![(root, 0, move_number_after(tree.node(root), 0))];
+        while let Some((id, depth, moves)) = stack.pop() {
+            let node = tree.node(id);
+            if self
+                .predicates
+                .iter()
+                .all(|p| p.matches(node, depth, moves))
+            {
+                hits.push(id);
+            }
+            for &child in node.children.iter().rev() {
+                stack.push((child, depth + 1, move_number_after(tree.node(child), moves)));
+            }
+        }
+    }
+}
+
+impl Predicate {
+    fn matches(&self, node: &TreeNode, depth: usize, move_number: usize) -> bool {
+        let props = &node.properties;
+        match self {
+            Predicate::HasProperty(tag) => props.iter().any(|p| p.tag() == tag),
+            Predicate::PropertyValue(tag, value) => props.iter().any(|p| {
+                p.tag() == tag && p.to_string()[tag.len()..].contains(&format!("[{value}]"))
+            }),
+            Predicate::Move { color, coord } => props.iter().any(|p| {
+                let (c, at) = match p {
+                    SGFProperty::B(at) => (Cell::Black, at),
+                    SGFProperty::W(at) => (Cell::White, at),
+                    _ => return false,
+                };
+                color.is_none_or(|color| color == c) && coord.is_none_or(|coord| coord == *at)
+            }),
+            Predicate::CommentContains(text) => comment(node).is_some_and(|c| c.contains(text)),
+            Predicate::CommentMatches(re) => comment(node).is_some_and(|c| re.is_match(c)),
+            Predicate::Depth(lo, hi) => (*lo, *hi).contains(&depth),
+            Predicate::MoveNumber(lo, hi) => (*lo, *hi).contains(&move_number),
+        }
+    }
+}
+
+fn comment(node: &TreeNode) -> Option<&str> {
+    node.properties.iter().find_map(|p| match p {
+        SGFProperty::C(s) => Some(s.as_str()),
+        _ => None,
+    })
+}
+
+/// The move number after `node`, given the number `before` it.  A move node
+/// with `MN` takes that number, as when replaying it onto a board.
+fn move_number_after(node: &TreeNode, before: usize) -> usize {
+    let moves = node
+        .properties
+        .iter()
+        .filter(|p| matches!(p, SGFProperty::B(_) | SGFProperty::W(_)))
+        .count();
+    match move_number_property(node) {
+        Some(number) if moves > 0 => number,
+        _ => before + moves,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    const SGF: &str = "(;GM[1]PB[Shusaku];B[pd]C[Joseki];W[dd]BM[1](;B[pp]C[slow joseki])(;B[pd]))\
+                       (;GM[1];B[pd];W[pp]C[joseki])";
+
+    #[test]
+    fn comment_and_move_predicates() {
+        let tree = parse_sgf(SGF).unwrap();
+        assert_eq!(Query::new().comment_contains("joseki").run(&tree).len(), 2);
+        assert_eq!(
+            Query::new()
+                .comment_matches("(?i)^joseki$")
+                .unwrap()
+                .run(&tree)
+                .len(),
+            2
+        );
+        let pd = GoCoord::new('p', 'd').unwrap();
+        assert_eq!(Query::new().move_at(pd).run(&tree).len(), 3);
+        assert_eq!(
+            Query::new()
+                .move_at(pd)
+                .player(Cell::Black)
+                .move_number(2..)
+                .run(&tree)
+                .len(),
+            1
+        );
+        assert!(Query::new().comment_matches("(").is_err());
+    }
+
+    #[test]
+    fn property_and_depth_predicates() {
+        let tree = parse_sgf(SGF).unwrap();
+        let bm = Query::new().has_property("BM").run(&tree);
+        assert_eq!(bm.len(), 1);
+        assert_eq!(
+            Query::new().property_value("PB", "Shusaku").run(&tree),
+            vec![tree.roots[0]]
+        );
+        assert_eq!(Query::new().depth(3..=3).run(&tree).len(), 2);
+        assert_eq!(Query::new().depth(..1).run(&tree), tree.roots);
+    }
+
+    #[test]
+    fn move_number_follows_mn() {
+        let tree = parse_sgf("(;GM[1];B[pd];W[dd]MN[50];B[pp];W[dp]C[x])").unwrap();
+        let node = |depth| Query::new().depth(depth..=depth).run(&tree);
+        assert_eq!(Query::new().move_number(50..=50).run(&tree), node(2));
+        assert_eq!(Query::new().move_number(52..).run(&tree), node(4));
+        assert!(Query::new().move_number(3..=4).run(&tree).is_empty());
+
+        let board = crate::sgf::Board::from_tree(&tree, node(4)[0]);
+        assert_eq!(board.move_number, 52);
+    }
+
+    #[test]
+    fn find_next_wraps_around() {
+        let tree = parse_sgf(SGF).unwrap();
+        let query = Query::new().comment_contains("joseki");
+        let hits = query.run(&tree);
+        assert_eq!(query.find_next(&tree, tree.roots[0]), Some(hits[0]));
+        assert_eq!(query.find_next(&tree, hits[0]), Some(hits[1]));
+        assert_eq!(query.find_next(&tree, hits[1]), Some(hits[0]));
+        assert_eq!(Query::next_hit(&tree, &hits, hits[0]), Some(hits[1]));
+        assert_eq!(Query::next_hit(&tree, &[], hits[0]), None);
+    }
+}
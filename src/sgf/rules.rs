@@ -0,0 +1,131 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{Result, bail};
+
+use crate::sgf::SuperkoRule;
+
+/// A ruleset, as named by the SGF `RU` property.
+///
+/// [`Board`](crate::sgf::Board) picks this up from `RU` while replaying a
+/// game and uses it for capture and legality decisions.  Games without an
+/// `RU` property (or with an unrecognised one) use [`Rules::Japanese`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rules {
+    /// Territory scoring, simple ko only, no suicide.
+    #[default]
+    Japanese,
+    /// Area scoring, positional superko, no suicide.
+    Chinese,
+    /// Area scoring, situational superko, no suicide.
+    Aga,
+    /// Area scoring, situational superko, multi-stone suicide allowed.
+    NewZealand,
+    /// Area scoring, positional superko, multi-stone suicide allowed.
+    TrompTaylor,
+}
+
+/// How the final score is counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scoring {
+    /// Stones plus surrounded empty points.
+    Area,
+    /// Surrounded empty points plus prisoners.
+    Territory,
+}
+
+impl Rules {
+    /// Whether a move may capture its own group.  Single-stone suicide still
+    /// recreates the previous position, so it is always forbidden by the
+    /// ruleset's superko rule.
+    pub fn allows_suicide(self) -> bool {
+        matches!(self, Rules::NewZealand | Rules::TrompTaylor)
+    }
+
+    /// The superko rule in force, or `None` if only simple ko applies.
+    pub fn superko(self) -> Option<SuperkoRule> {
+        match self {
+            Rules::Japanese => None,
+            Rules::Chinese | Rules::TrompTaylor => Some(SuperkoRule::Positional),
+            Rules::Aga | Rules::NewZealand => Some(SuperkoRule::Situational),
+        }
+    }
+
+    /// The counting method used to score the game.
+    pub fn scoring(self) -> Scoring {
+        match self {
+            Rules::Japanese => Scoring::Territory,
+            _ => Scoring::Area,
+        }
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Rules::Japanese => "Japanese",
+            Rules::Chinese => "Chinese",
+            Rules::Aga => "AGA",
+            Rules::NewZealand => "NZ",
+            Rules::TrompTaylor => "Tromp-Taylor",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for Rules {
+    type Err = anyhow::Error;
+
+    /// Parse an `RU` value.  Matching is case-insensitive and accepts the
+    /// common spellings found in the wild (`"Japanese"`, `"jp"`, `"NZ"`,
+    /// `"New Zealand"`, `"Tromp Taylor"`, ...).
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_ascii_lowercase().as_str() {
+            "japanese" | "jp" | "j" => Rules::Japanese,
+            "chinese" | "cn" | "c" => Rules::Chinese,
+            "aga" => Rules::Aga,
+            "nz" | "new zealand" | "newzealand" => Rules::NewZealand,
+            "tromp-taylor" | "tromp taylor" | "tromptaylor" | "tt" => Rules::TrompTaylor,
+            _ => bail!("Unknown ruleset {:?}", s),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{Board, Cell, parse_sgf};
+
+    #[test]
+    fn parses_ru_spellings() {
+        assert_eq!("Japanese".parse::<Rules>().unwrap(), Rules::Japanese);
+        assert_eq!(" new zealand".parse::<Rules>().unwrap(), Rules::NewZealand);
+        assert_eq!("TT".parse::<Rules>().unwrap(), Rules::TrompTaylor);
+        assert!("Ing".parse::<Rules>().is_err());
+        for rules in [Rules::Chinese, Rules::Aga, Rules::TrompTaylor] {
+            assert_eq!(rules.to_string().parse::<Rules>().unwrap(), rules);
+        }
+    }
+
+    #[test]
+    fn multi_stone_suicide_depends_on_rules() {
+        // Black's two stones at aa/ba are left with one liberty at ca; filling
+        // it captures nothing.
+        let sgf = |ru: &str| format!("(;RU[{ru}]AB[aa][ba]AW[ab][bb][da][cb];B[ca])");
+
+        let tree = parse_sgf(&sgf("Japanese")).unwrap();
+        let id = tree.iter_leaves(tree.roots[0]).next().unwrap().0;
+        let board = Board::from_tree(&tree, id);
+        assert_eq!(board.rules, Rules::Japanese);
+        assert_eq!(board.cells[0][2], Cell::Black);
+
+        let tree = parse_sgf(&sgf("NZ")).unwrap();
+        let id = tree.iter_leaves(tree.roots[0]).next().unwrap().0;
+        let board = Board::from_tree(&tree, id);
+        assert_eq!(board.rules, Rules::NewZealand);
+        assert!((0..3).all(|col| board.cells[0][col] == Cell::Empty));
+        assert_eq!(board.captured_white, 3);
+    }
+}
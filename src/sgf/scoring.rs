@@ -0,0 +1,313 @@
+use crate::sgf::{Board, Cell, Rules, SGFProperty, Scoring, node::GoCoord};
+
+/// Most liberties outside its eye that a chain in seki can have.
+const SEKI_LIBERTIES: usize = 2;
+
+/// The counted result of a finished position; see [`Board::score`].
+#[derive(Debug, Clone)]
+pub struct Score {
+    /// Counting method used, from the rules passed to
+    /// [`Board::score_with`].
+    pub scoring: Scoring,
+    /// Komi added to white's total.
+    pub komi: f64,
+    /// Black's points: area, or territory plus prisoners.
+    pub black: f64,
+    /// White's points including komi.
+    pub white: f64,
+    /// Points owned by black, including points under dead white stones.
+    pub black_territory: Vec<(usize, usize)>,
+    /// Points owned by white, including points under dead black stones.
+    pub white_territory: Vec<(usize, usize)>,
+    /// Empty points owned by nobody: dame, shared liberties in seki and,
+    /// under territory scoring, eyes of groups in seki.
+    pub dame: Vec<(usize, usize)>,
+    /// Stones judged to be in seki.
+    pub seki: Vec<(usize, usize)>,
+}
+
+impl Score {
+    /// Black's lead; negative when white is ahead.
+    pub fn margin(&self) -> f64 {
+        self.black - self.white
+    }
+
+    /// The result as an SGF `RE` value, e.g. `"B+3.5"`, `"W+12"` or `"0"`
+    /// for a draw.
+    pub fn result(&self) -> String {
+        let margin = self.margin();
+        if margin > 0.0 {
+            format!("B+{}", margin)
+        } else if margin < 0.0 {
+            format!("W+{}", -margin)
+        } else {
+            "0".to_string()
+        }
+    }
+
+    /// `TB` and `TW` properties marking each side's territory, omitting
+    /// empty ones.
+    pub fn markup(&self) -> Vec<SGFProperty> {
+        [("TB", &self.black_territory), ("TW", &self.white_territory)]
+            .into_iter()
+            .filter(|(_, points)| !points.is_empty())
+            .map(|(tag, points)| {
+                let values = points
+                    .iter()
+                    .map(|&(row, col)| GoCoord::from_colrow(col, row).to_string())
+                    .collect();
+                SGFProperty::Unknown(tag.to_string(), values)
+            })
+            .collect()
+    }
+}
+
+impl Board {
+    /// Score the position with the stones in `dead` removed, using
+    /// [`Board::rules`] and [`Board::komi`].
+    ///
+    /// Marking any stone of a chain marks the whole chain dead.  Dead stones
+    /// are taken off the board and count as prisoners under territory
+    /// scoring, so their points become the capturer's territory.
+    ///
+    /// An empty region counts for a side when only that side's stones border
+    /// it.  Seki is recognised heuristically: a chain is a seki candidate
+    /// when it borders at most one owned (eye) region and has at most two
+    /// liberties in neutral regions, and a neutral region is a shared seki
+    /// liberty when it borders chains of both colours and every chain
+    /// bordering it is a candidate.  Under territory scoring the eyes of
+    /// chains in seki are not counted; under area scoring they are.
+    /// Japanese-style counting assumes dame have been filled.
+    pub fn score(&self, dead: &[(usize, usize)]) -> Score {
+        self.score_with(dead, self.rules)
+    }
+
+    /// [`Board::score`] under `rules` instead of [`Board::rules`].
+    pub fn score_with(&self, dead: &[(usize, usize)], rules: Rules) -> Score {
+        let mut board = self.scratch();
+        let (mut dead_black, mut dead_white) = (0u16, 0u16);
+        let before = self.chains();
+        let mut removed = vec![false; before.chains().len()];
+        for &(row, col) in dead {
+            let Some(id) = before.chain_at(row, col) else {
+                continue;
+            };
+            if std::mem::replace(&mut removed[id], true) {
+                continue;
+            }
+            let chain = before.chain(id);
+            let n = chain.stones.len() as u16;
+            match chain.color {
+                Cell::Black => dead_black += n,
+                Cell::White => dead_white += n,
+                Cell::Empty => {}
+            }
+            for &(r, c) in &chain.stones {
+                board.cells[r][c] = Cell::Empty;
+            }
+        }
+
+        let chains = board.chains();
+        let regions = board.areas(&chains, |cell| cell == Cell::Empty);
+        let mut region_at = [[0; 19]; 19];
+        for (region, area) in regions.iter().enumerate() {
+            for &(r, c) in &area.points {
+                region_at[r][c] = region;
+            }
+        }
+        // The only colour bordering each region, or `Empty` if both or
+        // neither do.
+        let owner: Vec<Cell> = regions
+            .iter()
+            .map(|area| {
+                let mut colors = area.border.iter().map(|&id| chains.chain(id).color);
+                match colors.next() {
+                    Some(first) if colors.all(|color| color == first) => first,
+                    _ => Cell::Empty,
+                }
+            })
+            .collect();
+
+        // Chains short of liberties, touching a neutral region and at most
+        // one owned region.
+        let candidate: Vec<bool> = chains
+            .chains()
+            .iter()
+            .map(|chain| {
+                let mut touched: Vec<usize> = chain
+                    .liberties
+                    .iter()
+                    .map(|&(r, c)| region_at[r][c])
+                    .collect();
+                touched.sort_unstable();
+                touched.dedup();
+                let neutral = touched.iter().filter(|&&r| owner[r] == Cell::Empty).count();
+                let neutral_liberties = chain
+                    .liberties
+                    .iter()
+                    .filter(|&&(r, c)| owner[region_at[r][c]] == Cell::Empty)
+                    .count();
+                neutral > 0 && touched.len() - neutral <= 1 && neutral_liberties <= SEKI_LIBERTIES
+            })
+            .collect();
+        let mut in_seki = vec![false; chains.chains().len()];
+        for (region, area) in regions.iter().enumerate() {
+            if owner[region] != Cell::Empty {
+                continue;
+            }
+            let has = |color| area.border.iter().any(|&c| chains.chain(c).color == color);
+            if has(Cell::Black) && has(Cell::White) && area.border.iter().all(|&c| candidate[c]) {
+                for &c in &area.border {
+                    in_seki[c] = true;
+                }
+            }
+        }
+
+        let mut score = Score {
+            scoring: rules.scoring(),
+            komi: self.komi,
+            black: 0.0,
+            white: self.komi,
+            black_territory: Vec::new(),
+            white_territory: Vec::new(),
+            dame: Vec::new(),
+            seki: Vec::new(),
+        };
+        for (region, area) in regions.iter().enumerate() {
+            let mut owner = owner[region];
+            if rules.scoring() == Scoring::Territory && area.border.iter().any(|&c| in_seki[c]) {
+                owner = Cell::Empty;
+            }
+            let list = match owner {
+                Cell::Black => &mut score.black_territory,
+                Cell::White => &mut score.white_territory,
+                Cell::Empty => &mut score.dame,
+            };
+            list.extend(&area.points);
+        }
+        for (chain, &seki) in chains.chains().iter().zip(&in_seki) {
+            if seki {
+                score.seki.extend(&chain.stones);
+            }
+        }
+        for list in [
+            &mut score.black_territory,
+            &mut score.white_territory,
+            &mut score.dame,
+            &mut score.seki,
+        ] {
+            list.sort_unstable();
+        }
+
+        score.black += score.black_territory.len() as f64;
+        score.white += score.white_territory.len() as f64;
+        match rules.scoring() {
+            Scoring::Area => {
+                for row in board.cells.iter().take(board.size) {
+                    for &cell in row.iter().take(board.size) {
+                        match cell {
+                            Cell::Black => score.black += 1.0,
+                            Cell::White => score.white += 1.0,
+                            Cell::Empty => {}
+                        }
+                    }
+                }
+            }
+            Scoring::Territory => {
+                score.black += f64::from(self.captured_black + dead_white);
+                score.white += f64::from(self.captured_white + dead_black);
+            }
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{Rules, parse_sgf};
+
+    fn board(sgf: &str) -> Board {
+        let tree = parse_sgf(sgf).unwrap();
+        Board::from_tree(&tree, tree.roots[0])
+    }
+
+    fn fill_column(board: &mut Board, col: usize, color: Cell) {
+        for row in 0..board.size {
+            board.cells[row][col] = color;
+        }
+    }
+
+    #[test]
+    fn territory_and_area_with_dead_stones() {
+        let mut b = board("(;KM[6.5])");
+        fill_column(&mut b, 9, Cell::Black);
+        fill_column(&mut b, 10, Cell::White);
+        b.cells[0][0] = Cell::White;
+        b.captured_black = 2;
+
+        let score = b.score(&[(0, 0)]);
+        assert_eq!(score.scoring, Scoring::Territory);
+        assert_eq!(score.black_territory.len(), 171);
+        assert_eq!(score.white_territory.len(), 152);
+        assert_eq!(score.black, 171.0 + 2.0 + 1.0);
+        assert_eq!(score.white, 152.0 + 6.5);
+        assert_eq!(score.result(), "B+15.5");
+
+        b.rules = Rules::Chinese;
+        let score = b.score(&[(0, 0)]);
+        assert_eq!(score.black, 190.0);
+        assert_eq!(score.white, 171.0 + 6.5);
+        assert_eq!(score.result(), "B+12.5");
+    }
+
+    #[test]
+    fn seki_eyes_are_not_territory() {
+        // A black chain with an eye at (0,0) and a white chain with an
+        // eye at (0,4) share the liberty (0,2); black owns the rest.
+        //   . X . O . O X . .
+        //   X X X O O O X . .
+        //   O O O O X X X . .
+        //   X X X X X . . . .
+        let b = board(
+            "(;SZ[9]KM[6.5]AB[ba][ab][bb][cb][ga][gb][ec][fc][gc][ad][bd][cd][dd][ed]\
+             AW[ac][bc][cc][dc][db][eb][fb][da][fa])",
+        );
+        let score = b.score(&[]);
+        assert_eq!(score.seki.len(), 4 + 9);
+        assert_eq!(score.dame, vec![(0, 0), (0, 2), (0, 4)]);
+        assert_eq!(score.black_territory.len(), 55);
+        assert!(score.white_territory.is_empty());
+        assert_eq!(score.result(), "B+48.5");
+
+        let score = b.score_with(&[], Rules::Chinese);
+        assert_eq!(score.scoring, Scoring::Area);
+        assert_eq!(score.dame, vec![(0, 2)]);
+        assert_eq!(score.black, 56.0 + 14.0);
+        assert_eq!(score.white, 1.0 + 9.0 + 6.5);
+    }
+
+    #[test]
+    fn open_dame_is_not_seki() {
+        let mut b = board("(;KM[6.5])");
+        fill_column(&mut b, 9, Cell::Black);
+        fill_column(&mut b, 11, Cell::White);
+        let score = b.score(&[]);
+        assert!(score.seki.is_empty());
+        assert_eq!(score.dame.len(), 19);
+        assert_eq!(score.black_territory.len(), 9 * 19);
+        assert_eq!(score.white_territory.len(), 7 * 19);
+    }
+
+    #[test]
+    fn markup_lists_territory() {
+        let mut b = board("(;RU[Chinese])");
+        fill_column(&mut b, 17, Cell::Black);
+        fill_column(&mut b, 1, Cell::White);
+        let markup = b.score(&[]).markup();
+        assert_eq!(markup.len(), 2);
+        assert!(markup[0].to_string().starts_with("TB[sa][sb]"));
+        assert!(markup[1].to_string().starts_with("TW[aa][ab]"));
+        assert_eq!(markup[1].to_string().len(), 2 + 19 * 4);
+    }
+}
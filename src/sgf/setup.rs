@@ -0,0 +1,310 @@
+use anyhow::{Result, bail, ensure};
+
+use crate::sgf::{
+    GameTree, NodeId, Rules, SGFProperty,
+    board::{coord_col, coord_row},
+    node::{FileFormat, GameType, GoCoord, Komi},
+};
+
+/// Handicap stones for a new game.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Handicap {
+    /// An even game: no stones, black plays first.
+    #[default]
+    Even,
+    /// The standard placement of this many stones (2–9) on the star points,
+    /// as given by [`fixed_handicap`].
+    Fixed(u8),
+    /// Stones placed wherever black chose.  At least two distinct points.
+    Free(Vec<GoCoord>),
+}
+
+/// The traditional fixed handicap points for 2–9 stones on a 9×9, 13×13 or
+/// 19×19 board, in placement order.
+///
+/// Two stones go on the upper-right and lower-left star points, the third
+/// lower right and the fourth upper left.  Five adds the centre; six and
+/// seven use the left and right side points instead (seven with the
+/// centre); eight and nine add the top and bottom side points (nine with
+/// the centre).
+///
+/// ```
+/// use tesuji::sgf::fixed_handicap;
+///
+/// let stones = fixed_handicap(19, 2).unwrap();
+/// let points: Vec<String> = stones.iter().map(|c| c.to_string()).collect();
+/// assert_eq!(points, ["pd", "dp"]);
+/// assert!(fixed_handicap(15, 4).is_err());
+/// ```
+pub fn fixed_handicap(size: usize, stones: u8) -> Result<Vec<GoCoord>> {
+    let (near, far) = match size {
+        9 => (2, 6),
+        13 => (3, 9),
+        19 => (3, 15),
+        _ => bail!("Fixed handicap is only defined for 9x9, 13x13 and 19x19 boards"),
+    };
+    ensure!(
+        (2..=9).contains(&stones),
+        "Fixed handicap must be 2 to 9 stones"
+    );
+    let mid = size / 2;
+    // (row, col), row 0 at the top.
+    let corners = [(near, far), (far, near), (far, far), (near, near)];
+    let sides = [(mid, near), (mid, far)];
+    let top_bottom = [(near, mid), (far, mid)];
+    let center = (mid, mid);
+
+    let stones = usize::from(stones);
+    let mut points = corners[..stones.min(4)].to_vec();
+    if stones >= 6 {
+        points.extend(sides);
+    }
+    if stones >= 8 {
+        points.extend(top_bottom);
+    }
+    if stones % 2 == 1 && stones >= 5 {
+        points.push(center);
+    }
+    Ok(points
+        .into_iter()
+        .map(|(row, col)| GoCoord::from_colrow(col, row))
+        .collect())
+}
+
+/// Builder for the root node of a new game.
+///
+/// ```
+/// use tesuji::sgf::{Board, Cell, GameSetup, Handicap, Rules};
+///
+/// let tree = GameSetup::new()
+///     .size(13)
+///     .komi(0.5)
+///     .handicap(Handicap::Fixed(3))
+///     .rules(Rules::Japanese)
+///     .players("Black player", "White player")
+///     .build()
+///     .unwrap();
+/// let board = Board::from_tree(&tree, tree.roots[0]);
+/// assert_eq!(board.cells[3][9], Cell::Black);
+/// assert_eq!(board.to_play, Cell::White);
+/// ```
+///
+/// A game with handicap stones gets `HA` and an `AB` setup; fixed and free
+/// handicap games also get `PL[W]`, since white moves first.  Komi is not
+/// adjusted for the handicap.
+#[derive(Debug, Clone)]
+pub struct GameSetup {
+    size: u8,
+    komi: Komi,
+    handicap: Handicap,
+    rules: Option<Rules>,
+    black: Option<String>,
+    white: Option<String>,
+}
+
+impl Default for GameSetup {
+    fn default() -> Self {
+        Self {
+            size: 19,
+            komi: Komi::default(),
+            handicap: Handicap::Even,
+            rules: None,
+            black: None,
+            white: None,
+        }
+    }
+}
+
+impl GameSetup {
+    /// An even 19×19 game with 6.5 komi, no `RU` and no player names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn size(mut self, size: u8) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn komi(mut self, komi: f64) -> Self {
+        self.komi = Komi::new(komi);
+        self
+    }
+
+    pub fn handicap(mut self, handicap: Handicap) -> Self {
+        self.handicap = handicap;
+        self
+    }
+
+    /// Record the ruleset as `RU`.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    /// Record the player names as `PB` and `PW`.
+    pub fn players(mut self, black: &str, white: &str) -> Self {
+        self.black = Some(black.to_string());
+        self.white = Some(white.to_string());
+        self
+    }
+
+    /// The root node properties.  Fails if the size is outside 1–19 or the
+    /// handicap does not fit the board.
+    pub fn properties(&self) -> Result<Vec<SGFProperty>> {
+        let size = usize::from(self.size);
+        ensure!(
+            (1..=19).contains(&size),
+            "Board size must be between 1 and 19"
+        );
+        let stones = match &self.handicap {
+            Handicap::Even => Vec::new(),
+            Handicap::Fixed(n) => fixed_handicap(size, *n)?,
+            Handicap::Free(points) => {
+                ensure!(points.len() >= 2, "Free handicap needs at least 2 stones");
+                for (i, p) in points.iter().enumerate() {
+                    ensure!(
+                        !p.is_pass() && coord_row(*p) < size && coord_col(*p) < size,
+                        "Handicap stone {} is off the board",
+                        p
+                    );
+                    ensure!(
+                        !points[..i].contains(p),
+                        "Handicap stone {} is placed twice",
+                        p
+                    );
+                }
+                points.clone()
+            }
+        };
+
+        let mut props = vec![
+            SGFProperty::GM(GameType::Go),
+            SGFProperty::FF(FileFormat::FF4),
+            SGFProperty::SZ(self.size),
+            SGFProperty::KM(self.komi.clone()),
+        ];
+        if let Some(rules) = self.rules {
+            props.push(SGFProperty::Unknown(
+                "RU".to_string(),
+                vec![rules.to_string()],
+            ));
+        }
+        if let Some(black) = &self.black {
+            props.push(SGFProperty::PB(black.clone()));
+        }
+        if let Some(white) = &self.white {
+            props.push(SGFProperty::PW(white.clone()));
+        }
+        if !stones.is_empty() {
+            props.push(SGFProperty::HA(stones.len() as u8));
+            props.push(SGFProperty::AB(stones));
+            props.push(SGFProperty::Unknown(
+                "PL".to_string(),
+                vec!["W".to_string()],
+            ));
+        }
+        Ok(props)
+    }
+
+    /// A new tree holding just this game's root.
+    pub fn build(&self) -> Result<GameTree> {
+        let props = self.properties()?;
+        let mut tree = GameTree::new();
+        tree.node_mut(tree.roots[0]).properties = props;
+        Ok(tree)
+    }
+
+    /// Append this game to a collection as a new root.
+    pub fn add_to(&self, tree: &mut GameTree) -> Result<NodeId> {
+        Ok(tree.add_root(self.properties()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{Board, Cell, write_sgf};
+
+    #[test]
+    fn fixed_placements_use_star_points() {
+        let points = |size, n| -> Vec<String> {
+            fixed_handicap(size, n)
+                .unwrap()
+                .iter()
+                .map(|c| c.to_string())
+                .collect()
+        };
+        assert_eq!(points(19, 3), ["pd", "dp", "pp"]);
+        assert_eq!(points(19, 5), ["pd", "dp", "pp", "dd", "jj"]);
+        assert_eq!(points(19, 6), ["pd", "dp", "pp", "dd", "dj", "pj"]);
+        assert_eq!(
+            points(19, 9),
+            ["pd", "dp", "pp", "dd", "dj", "pj", "jd", "jp", "jj"]
+        );
+        assert_eq!(points(13, 7).len(), 7);
+        assert_eq!(points(9, 4), ["gc", "cg", "gg", "cc"]);
+        assert!(fixed_handicap(19, 1).is_err());
+        assert!(fixed_handicap(19, 10).is_err());
+    }
+
+    #[test]
+    fn setup_writes_root_properties() {
+        let tree = GameSetup::new().build().unwrap();
+        assert_eq!(write_sgf(&tree), "(;GM[1]FF[4]SZ[19]KM[6.5])");
+
+        let free = vec!["aa".parse().unwrap(), "ss".parse().unwrap()];
+        let mut tree = GameTree::new();
+        let root = GameSetup::new()
+            .komi(0.5)
+            .rules(Rules::Chinese)
+            .handicap(Handicap::Free(free))
+            .add_to(&mut tree)
+            .unwrap();
+        let out = write_sgf(&tree);
+        assert!(out.contains("RU[Chinese]HA[2]AB[aa][ss]PL[W]"), "{out}");
+        let board = Board::from_tree(&tree, root);
+        assert_eq!(board.cells[18][18], Cell::Black);
+        assert_eq!(board.to_play, Cell::White);
+
+        let dup = vec!["aa".parse().unwrap(), "aa".parse().unwrap()];
+        assert!(
+            GameSetup::new()
+                .handicap(Handicap::Free(dup))
+                .build()
+                .is_err()
+        );
+        let off_board = vec!["aa".parse().unwrap(), "kk".parse().unwrap()];
+        let small = GameSetup::new().size(9);
+        assert!(
+            small
+                .clone()
+                .handicap(Handicap::Free(off_board))
+                .build()
+                .is_err()
+        );
+        assert!(small.handicap(Handicap::Fixed(5)).build().is_ok());
+    }
+
+    #[test]
+    fn small_boards_score_their_own_points() {
+        let tree = GameSetup::new()
+            .size(9)
+            .rules(Rules::Chinese)
+            .build()
+            .unwrap();
+        let mut board = Board::from_tree(&tree, tree.roots[0]);
+        assert_eq!(board.size, 9);
+        assert_eq!(board.score(&[]).dame.len(), 81);
+
+        for row in 0..9 {
+            board
+                .play(Cell::Black, GoCoord::from_colrow(4, row))
+                .unwrap();
+        }
+        let score = board.score(&[]);
+        assert_eq!(score.black, 81.0);
+        assert_eq!(score.black_territory.len(), 72);
+        assert_eq!(score.result(), "B+74.5");
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use crate::sgf::{Board, Cell, GameTree, NodeId, Rules, TreeNode, board::CellChange};
+
+/// Everything needed to take one applied node back off a [`Board`].
+struct Delta {
+    /// Every cell change, in the order made.
+    cells: Vec<CellChange>,
+    move_number: usize,
+    captured_white: u16,
+    captured_black: u16,
+    ko_point: Option<(usize, usize)>,
+    to_play: Cell,
+    size: usize,
+    history_len: usize,
+    rules: Rules,
+    komi: f64,
+}
+
+/// A [`Board`] that can step forwards and backwards one node at a time.
+///
+/// [`BoardState::apply`] records the stones a node placed and captured, so
+/// [`BoardState::unapply`] restores the previous position in time
+/// proportional to that change rather than replaying from the root.
+///
+/// ```
+/// use tesuji::parse_sgf;
+/// use tesuji::sgf::BoardState;
+///
+/// let tree = parse_sgf("(;GM[1];B[dd];W[pp])").unwrap();
+/// let mut state = BoardState::new();
+/// for (_, node) in tree.iter_mainline(tree.roots[0]) {
+///     state.apply(node);
+/// }
+/// assert_eq!(state.board().move_number, 2);
+/// state.unapply();
+/// assert_eq!(state.board().move_number, 1);
+/// ```
+#[derive(Default)]
+pub struct BoardState {
+    board: Board,
+    undo: Vec<Delta>,
+}
+
+impl BoardState {
+    /// Start from an empty board.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from an existing position.  Nodes applied before this point
+    /// cannot be unapplied.
+    pub fn from_board(board: Board) -> Self {
+        Self {
+            board,
+            undo: Vec::new(),
+        }
+    }
+
+    /// The current position.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Consume the state, returning the current position.
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+
+    /// Number of applied nodes that [`BoardState::unapply`] can undo.
+    pub fn depth(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Apply `node`'s moves and setup stones to the position.
+    pub fn apply(&mut self, node: &TreeNode) {
+        let board = &mut self.board;
+        let mut delta = Delta {
+            cells: Vec::new(),
+            move_number: board.move_number,
+            captured_white: board.captured_white,
+            captured_black: board.captured_black,
+            ko_point: board.ko_point,
+            to_play: board.to_play,
+            size: board.size,
+            history_len: board.history.len(),
+            rules: board.rules,
+            komi: board.komi,
+        };
+        delta.cells = board.apply_node_journaled(node);
+        self.undo.push(delta);
+    }
+
+    /// Undo the most recently applied node.  Returns `false` if there is
+    /// nothing to undo.
+    pub fn unapply(&mut self) -> bool {
+        let Some(delta) = self.undo.pop() else {
+            return false;
+        };
+        let board = &mut self.board;
+        for &change in delta.cells.iter().rev() {
+            board.restore_cell(change);
+        }
+        board.move_number = delta.move_number;
+        board.captured_white = delta.captured_white;
+        board.captured_black = delta.captured_black;
+        board.ko_point = delta.ko_point;
+        board.to_play = delta.to_play;
+        board.size = delta.size;
+        board.history.truncate(delta.history_len);
+        board.rules = delta.rules;
+        board.komi = delta.komi;
+        true
+    }
+}
+
+/// A [`BoardState`] kept in step with a cursor in a [`GameTree`].
+///
+/// [`BoardCursor::seek`] moves to any node by unapplying back to the
+/// closest common ancestor and applying down to the target, so stepping to
+/// a parent, child or nearby variation costs only the nodes in between.
+///
+/// The cursor caches the path it has applied; after the tree is edited
+/// above or along that path, call [`BoardCursor::reset`].
+pub struct BoardCursor {
+    state: BoardState,
+    /// Root-to-cursor path whose nodes are applied to `state`.
+    path: Vec<NodeId>,
+    /// Index of each node in `path`.
+    index: HashMap<NodeId, usize>,
+}
+
+impl BoardCursor {
+    /// Position the cursor at `id`, replaying from its root.
+    pub fn new(tree: &GameTree, id: NodeId) -> Self {
+        let mut cursor = Self {
+            state: BoardState::new(),
+            path: Vec::new(),
+            index: HashMap::new(),
+        };
+        cursor.seek(tree, id);
+        cursor
+    }
+
+    /// The node whose position is on the board, if any.
+    pub fn node(&self) -> Option<NodeId> {
+        self.path.last().copied()
+    }
+
+    /// The position at [`BoardCursor::node`].
+    pub fn board(&self) -> &Board {
+        self.state.board()
+    }
+
+    /// The root-to-cursor path of applied nodes.
+    pub fn path(&self) -> &[NodeId] {
+        &self.path
+    }
+
+    /// Move to `target`, unapplying and applying only the nodes between the
+    /// current node and `target`.
+    pub fn seek(&mut self, tree: &GameTree, target: NodeId) {
+        // Walk up from the target until reaching a node already applied.
+        let mut pending = Vec::new();
+        let mut current = Some(target);
+        let keep = loop {
+            match current {
+                Some(id) => match self.index.get(&id) {
+                    Some(&i) => break i + 1,
+                    None => {
+                        pending.push(id);
+                        current = tree.node(id).parent;
+                    }
+                },
+                // Different game record: start over.
+                None => break 0,
+            }
+        };
+
+        while self.path.len() > keep {
+            let id = self.path.pop().expect("path is longer than keep");
+            self.index.remove(&id);
+            self.state.unapply();
+        }
+        for id in pending.into_iter().rev() {
+            self.state.apply(tree.node(id));
+            self.index.insert(id, self.path.len());
+            self.path.push(id);
+        }
+    }
+
+    /// Discard all cached state and replay from the root to `target`, e.g.
+    /// after the tree was edited.
+    pub fn reset(&mut self, tree: &GameTree, target: NodeId) {
+        *self = Self::new(tree, target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    const SGF: &str = "(;GM[1]AW[bb][dd]AB[ab][ba][bc][dc][cd][ed];B[cb];B[de](;W[cc]KM[6.5];B[bb])(;W[pp];B[qq]))";
+
+    fn assert_same(a: &Board, b: &Board) {
+        assert_eq!(a.cells, b.cells);
+        assert_eq!(a.hash, b.hash);
+        assert_eq!(a.move_number, b.move_number);
+        assert_eq!(a.move_numbers, b.move_numbers);
+        assert_eq!(a.captured_black, b.captured_black);
+        assert_eq!(a.captured_white, b.captured_white);
+        assert_eq!(a.ko_point, b.ko_point);
+        assert_eq!(a.to_play, b.to_play);
+        assert_eq!(a.size, b.size);
+        assert_eq!(a.history, b.history);
+        assert_eq!(a.komi, b.komi);
+    }
+
+    #[test]
+    fn unapply_restores_captures() {
+        let tree = parse_sgf(SGF).unwrap();
+        let mut state = BoardState::new();
+        let mainline: Vec<NodeId> = tree
+            .iter_mainline(tree.roots[0])
+            .map(|(id, _)| id)
+            .collect();
+        for &id in &mainline {
+            state.apply(tree.node(id));
+            assert_same(state.board(), &Board::from_tree(&tree, id));
+        }
+        assert_eq!(state.board().captured_black, 2);
+        for &id in mainline.iter().rev().skip(1) {
+            assert!(state.unapply());
+            assert_same(state.board(), &Board::from_tree(&tree, id));
+        }
+        assert!(state.unapply());
+        assert_same(state.board(), &Board::new());
+        assert!(!state.unapply());
+    }
+
+    #[test]
+    fn cursor_seeks_across_variations() {
+        let tree = parse_sgf(SGF).unwrap();
+        let ids: Vec<NodeId> = tree.iter_subtree(tree.roots[0]).map(|(id, _)| id).collect();
+        let mut cursor = BoardCursor::new(&tree, tree.roots[0]);
+        for &target in ids.iter().chain(ids.iter().rev()) {
+            cursor.seek(&tree, target);
+            assert_eq!(cursor.node(), Some(target));
+            assert_same(cursor.board(), &Board::from_tree(&tree, target));
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::sgf::{
+    Board, Cell, GameTree, SGFProperty,
+    board::{coord_col, coord_row},
+    node::GoCoord,
+};
+
+/// Properties whose values are points or `point:point` rectangles.
+const POINT_LIST_TAGS: &[&str] = &["AE", "CR", "DD", "MA", "SL", "SQ", "TB", "TR", "TW", "VW"];
+
+/// Properties whose values are `point:point` pairs (arrows and lines).
+const POINT_PAIR_TAGS: &[&str] = &["AR", "LN"];
+
+/// One of the eight rotations and reflections of a square board.
+///
+/// Transforms act on `(row, col)` with row 0 at the top, so
+/// [`Symmetry::Rotate90`] turns the board a quarter clockwise and
+/// [`Symmetry::Transpose`] swaps rows and columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirror left to right.
+    FlipHorizontal,
+    /// Mirror top to bottom.
+    FlipVertical,
+    /// Reflect in the top-left to bottom-right diagonal.
+    Transpose,
+    /// Reflect in the top-right to bottom-left diagonal.
+    AntiTranspose,
+}
+
+impl Symmetry {
+    /// All eight symmetries, [`Symmetry::Identity`] first.
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::Transpose,
+        Symmetry::AntiTranspose,
+    ];
+
+    /// The symmetry that undoes this one.
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Map `(row, col)` on a `size`×`size` board.
+    pub fn apply(self, row: usize, col: usize, size: usize) -> (usize, usize) {
+        let last = size - 1;
+        match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, last - row),
+            Symmetry::Rotate180 => (last - row, last - col),
+            Symmetry::Rotate270 => (last - col, row),
+            Symmetry::FlipHorizontal => (row, last - col),
+            Symmetry::FlipVertical => (last - row, col),
+            Symmetry::Transpose => (col, row),
+            Symmetry::AntiTranspose => (last - col, last - row),
+        }
+    }
+}
+
+impl GoCoord {
+    /// This coordinate under `symmetry` on a `size`×`size` board.  Passes are
+    /// left unchanged.
+    pub fn transformed(self, symmetry: Symmetry, size: usize) -> GoCoord {
+        if self.is_pass() {
+            return self;
+        }
+        let (row, col) = symmetry.apply(coord_row(self), coord_col(self), size);
+        GoCoord::from_colrow(col, row)
+    }
+}
+
+impl Board {
+    /// A copy of the position under `symmetry`.
+    ///
+    /// Stones, their move numbers and the ko point are moved and the hash
+    /// recomputed; the superko history is reduced to the current position,
+    /// since earlier positions are only known by their hashes.
+    pub fn transformed(&self, symmetry: Symmetry) -> Board {
+        let mut out = self.clone();
+        out.cells = [[Cell::Empty; 19]; 19];
+        out.move_numbers = [[None; 19]; 19];
+        for row in 0..self.size {
+            for col in 0..self.size {
+                let (r, c) = symmetry.apply(row, col, self.size);
+                out.cells[r][c] = self.cells[row][col];
+                out.move_numbers[r][c] = self.move_numbers[row][col];
+            }
+        }
+        out.ko_point = self
+            .ko_point
+            .map(|(row, col)| symmetry.apply(row, col, self.size));
+        out.rehash();
+        if !out.history.is_empty() {
+            out.history = vec![(out.hash, out.to_play)];
+        }
+        out
+    }
+
+    /// The representative of this position among its eight symmetric
+    /// variants, with the symmetry that produces it.
+    ///
+    /// The representative is the variant with the smallest
+    /// [`Board::hash`], so two positions that are rotations or reflections
+    /// of each other have the same canonical board and hash.
+    pub fn canonical(&self) -> (Board, Symmetry) {
+        Symmetry::ALL
+            .into_iter()
+            .map(|symmetry| (self.transformed(symmetry), symmetry))
+            .min_by_key(|(board, _)| board.hash)
+            .expect("ALL is not empty")
+    }
+
+    /// [`Board::hash`] of [`Board::canonical`], for looking up positions
+    /// regardless of orientation.
+    pub fn canonical_hash(&self) -> u64 {
+        self.canonical().0.hash
+    }
+}
+
+impl GameTree {
+    /// A copy of the tree with every game rotated or mirrored by `symmetry`.
+    ///
+    /// Moves, setup stones (`AB`, `AW`, `AE`), markup (`TR`, `SQ`, `CR`, `MA`,
+    /// `SL`, `DD`, `LB`, `AR`, `LN`), territory (`TB`, `TW`) and `VW` are
+    /// rewritten, using each game's `SZ` (default 19).  Compressed
+    /// `point:point` rectangles are normalised so the first corner is the
+    /// top left.  Values that are not valid points are kept as they are.
+    pub fn transformed(&self, symmetry: Symmetry) -> GameTree {
+        let mut out = self.clone();
+        for &root in &self.roots {
+            let size = self
+                .node(root)
+                .properties
+                .iter()
+                .find_map(|p| match p {
+                    SGFProperty::SZ(size) => Some(usize::from(*size)),
+                    _ => None,
+                })
+                .unwrap_or(19);
+            for (id, node) in self.iter_subtree(root) {
+                out.node_mut(id).properties = node
+                    .properties
+                    .iter()
+                    .map(|prop| transform_property(prop, symmetry, size))
+                    .collect();
+            }
+        }
+        out
+    }
+}
+
+fn transform_property(prop: &SGFProperty, symmetry: Symmetry, size: usize) -> SGFProperty {
+    let coord = |c: &GoCoord| c.transformed(symmetry, size);
+    match prop {
+        SGFProperty::B(c) => SGFProperty::B(coord(c)),
+        SGFProperty::W(c) => SGFProperty::W(coord(c)),
+        SGFProperty::AB(cs) => SGFProperty::AB(cs.iter().map(coord).collect()),
+        SGFProperty::AW(cs) => SGFProperty::AW(cs.iter().map(coord).collect()),
+        SGFProperty::Unknown(tag, values) => {
+            let values = values
+                .iter()
+                .map(|value| {
+                    let tag = tag.as_str();
+                    if POINT_LIST_TAGS.contains(&tag) {
+                        transform_rect(value, symmetry, size)
+                    } else if POINT_PAIR_TAGS.contains(&tag) {
+                        transform_pair(value, symmetry, size)
+                    } else if tag == "LB" {
+                        transform_label(value, symmetry, size)
+                    } else {
+                        None
+                    }
+                    .unwrap_or_else(|| value.clone())
+                })
+                .collect();
+            SGFProperty::Unknown(tag.clone(), values)
+        }
+        other => other.clone(),
+    }
+}
+
+fn transform_point(value: &str, symmetry: Symmetry, size: usize) -> Option<(usize, usize)> {
+    let coord: GoCoord = value.parse().ok()?;
+    let (row, col) = (coord_row(coord), coord_col(coord));
+    (row < size && col < size).then(|| symmetry.apply(row, col, size))
+}
+
+fn point_str((row, col): (usize, usize)) -> String {
+    GoCoord::from_colrow(col, row).to_string()
+}
+
+/// A point, or a `point:point` rectangle given by opposite corners.
+fn transform_rect(value: &str, symmetry: Symmetry, size: usize) -> Option<String> {
+    match value.split_once(':') {
+        None => transform_point(value, symmetry, size).map(point_str),
+        Some((a, b)) => {
+            let a = transform_point(a, symmetry, size)?;
+            let b = transform_point(b, symmetry, size)?;
+            let top_left = (a.0.min(b.0), a.1.min(b.1));
+            let bottom_right = (a.0.max(b.0), a.1.max(b.1));
+            Some(format!(
+                "{}:{}",
+                point_str(top_left),
+                point_str(bottom_right)
+            ))
+        }
+    }
+}
+
+/// A `from:to` pair, keeping its direction.
+fn transform_pair(value: &str, symmetry: Symmetry, size: usize) -> Option<String> {
+    let (a, b) = value.split_once(':')?;
+    let a = transform_point(a, symmetry, size)?;
+    let b = transform_point(b, symmetry, size)?;
+    Some(format!("{}:{}", point_str(a), point_str(b)))
+}
+
+/// A `point:text` label.
+fn transform_label(value: &str, symmetry: Symmetry, size: usize) -> Option<String> {
+    let (point, text) = value.split_once(':')?;
+    let point = transform_point(point, symmetry, size)?;
+    Some(format!("{}:{}", point_str(point), text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::{parse_sgf, write_sgf};
+
+    #[test]
+    fn symmetries_are_distinct_and_invertible() {
+        let images: Vec<_> = Symmetry::ALL.iter().map(|s| s.apply(0, 2, 19)).collect();
+        for (i, a) in images.iter().enumerate() {
+            assert!(images[i + 1..].iter().all(|b| a != b));
+        }
+        for symmetry in Symmetry::ALL {
+            let (r, c) = symmetry.apply(3, 15, 19);
+            assert_eq!(symmetry.inverse().apply(r, c, 19), (3, 15));
+        }
+        assert_eq!(Symmetry::Rotate90.apply(0, 0, 19), (0, 18));
+        let dd: GoCoord = "dd".parse().unwrap();
+        assert_eq!(dd.transformed(Symmetry::Rotate180, 19).to_string(), "pp");
+        assert_eq!(
+            dd.transformed(Symmetry::FlipHorizontal, 9).to_string(),
+            "fd"
+        );
+    }
+
+    #[test]
+    fn tree_rewrites_moves_setup_and_markup() {
+        let tree =
+            parse_sgf("(;SZ[9]AB[aa]AE[bb:cc];B[ab]LB[ac:hi]AR[aa:ib]TR[ba]C[keep me])").unwrap();
+        let out = write_sgf(&tree.transformed(Symmetry::FlipHorizontal));
+        assert!(out.contains("AB[ia]"), "{out}");
+        assert!(out.contains("AE[gb:hc]"), "{out}");
+        assert!(out.contains("B[ib]"), "{out}");
+        assert!(out.contains("LB[ic:hi]"), "{out}");
+        assert!(out.contains("AR[ia:ab]"), "{out}");
+        assert!(out.contains("TR[ha]"), "{out}");
+        assert!(out.contains("C[keep me]"), "{out}");
+    }
+
+    #[test]
+    fn canonical_board_ignores_orientation() {
+        let tree = parse_sgf("(;AB[cd][dc]AW[qp];B[pd])").unwrap();
+        let board = Board::from_tree(&tree, tree.iter_mainline(tree.roots[0]).last().unwrap().0);
+        let (canonical, _) = board.canonical();
+        for symmetry in Symmetry::ALL {
+            let other = board.transformed(symmetry);
+            assert_eq!(other.canonical_hash(), canonical.hash);
+            assert_eq!(other.canonical().0.cells, canonical.cells);
+        }
+        let (_, symmetry) = board.canonical();
+        assert_eq!(board.transformed(symmetry).cells, canonical.cells);
+    }
+
+    #[test]
+    fn small_boards_mirror_about_their_own_centre() {
+        let last = |sgf: &str| {
+            let tree = parse_sgf(sgf).unwrap();
+            Board::from_tree(&tree, tree.iter_mainline(tree.roots[0]).last().unwrap().0)
+        };
+        let a = last("(;SZ[9];B[cc])");
+        let b = last("(;SZ[9];B[gc])");
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+        assert_eq!(
+            b.transformed(Symmetry::FlipHorizontal).cells[2][2],
+            Cell::Black
+        );
+        assert_ne!(a.canonical_hash(), last("(;SZ[9];B[hc])").canonical_hash());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::sgf::{Board, Cell, GameTree, NodeId, SGFProperty, node::GoCoord};
+
+/// SGF markup properties (FF\[4\] "Markup" category).
+pub const MARKUP_TAGS: &[&str] = &["AR", "CR", "DD", "LB", "LN", "MA", "SL", "SQ", "TR"];
+
+/// Every property defined by the FF\[4\] specification for Go, including
+/// properties the parser keeps as [`SGFProperty::Unknown`].
+#[rustfmt::skip]
+pub const STANDARD_TAGS: &[&str] = &[
+    // Move and setup
+    "B", "KO", "MN", "W", "AB", "AE", "AW", "PL",
+    // Node annotation
+    "C", "DM", "GB", "GW", "HO", "N", "UC", "V",
+    // Move annotation
+    "BM", "DO", "IT", "TE",
+    // Markup
+    "AR", "CR", "DD", "LB", "LN", "MA", "SL", "SQ", "TR",
+    // Root
+    "AP", "CA", "FF", "GM", "ST", "SZ",
+    // Game info
+    "AN", "BR", "BT", "CP", "DT", "EV", "GN", "GC", "ON", "OT", "PB", "PC", "PW", "RE", "RO",
+    "RU", "SO", "TM", "US", "WR", "WT", "HA", "KM",
+    // Timing
+    "BL", "OB", "OW", "WL",
+    // Miscellaneous
+    "FG", "PM", "VW",
+    // Go-specific
+    "TB", "TW",
+];
+
+/// Properties that only describe how a position was reached, and so are
+/// dropped from the root of an [`extract_subtree`](GameTree::extract_subtree)
+/// result.
+const POSITION_TAGS: &[&str] = &["B", "W", "AB", "AW", "AE", "PL", "KO", "MN"];
+
+/// Transforms that produce a cleaned-up copy of a tree, e.g. before
+/// publishing a reviewed game.  None of them modify `self`.
+impl GameTree {
+    /// Keep only the main line (first child at every node) of each game.
+    pub fn mainline_only(&self) -> GameTree {
+        self.without_variations_deeper_than(0)
+    }
+
+    /// Drop variations nested more than `max_depth` levels deep.
+    ///
+    /// The main line has depth 0, a variation branching off it depth 1, a
+    /// variation branching off that depth 2, and so on.
+    pub fn without_variations_deeper_than(&self, max_depth: usize) -> GameTree {
+        self.filtered(
+            |idx, depth| depth + usize::from(idx > 0) <= max_depth,
+            |_| true,
+        )
+    }
+
+    /// Remove every comment (`C`) property.
+    pub fn without_comments(&self) -> GameTree {
+        self.filtered(|_, _| true, |p| !matches!(p, SGFProperty::C(_)))
+    }
+
+    /// Remove every markup property (see [`MARKUP_TAGS`]).
+    pub fn without_markup(&self) -> GameTree {
+        self.filtered(|_, _| true, |p| !MARKUP_TAGS.contains(&p.tag()))
+    }
+
+    /// Remove properties not defined by the FF\[4\] specification, such as
+    /// application-specific extensions (see [`STANDARD_TAGS`]).
+    pub fn without_nonstandard_properties(&self) -> GameTree {
+        self.filtered(|_, _| true, |p| STANDARD_TAGS.contains(&p.tag()))
+    }
+
+    /// Extract the continuation below `id` as a standalone single-game tree.
+    ///
+    /// The new root carries the original game-info properties plus the
+    /// position at `id` reconstructed as `AB`/`AW` setup stones and a `PL`
+    /// property for the side to move.  The children of `id` become the
+    /// children of the new root.
+    pub fn extract_subtree(&self, id: NodeId) -> GameTree {
+        let board = Board::from_tree(self, id);
+        let root = self.iter_ancestors(id).last().map_or(id, |(r, _)| r);
+
+        let mut props: Vec<SGFProperty> = self
+            .node(root)
+            .properties
+            .iter()
+            .filter(|p| !POSITION_TAGS.contains(&p.tag()) && !matches!(p, SGFProperty::C(_)))
+            .cloned()
+            .collect();
+        let stones = |color: Cell| -> Vec<GoCoord> {
+            (0..board.size)
+                .flat_map(|row| (0..board.size).map(move |col| (row, col)))
+                .filter(|&(row, col)| board.cells[row][col] == color)
+                .map(|(row, col)| GoCoord::from_colrow(col, row))
+                .collect()
+        };
+        let (black, white) = (stones(Cell::Black), stones(Cell::White));
+        if !black.is_empty() {
+            props.push(SGFProperty::AB(black));
+        }
+        if !white.is_empty() {
+            props.push(SGFProperty::AW(white));
+        }
+        let player = if board.to_play == Cell::White {
+            "W"
+        } else {
+            "B"
+        };
+        props.push(SGFProperty::Unknown(
+            "PL".to_string(),
+            vec![player.to_string()],
+        ));
+
+        let mut out = GameTree {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+        let new_root = out.add_root(props);
+        for &child in &self.node(id).children {
+            out.graft_subtree(self, child, new_root);
+        }
+        out
+    }
+
+    /// Copy the tree, keeping only children accepted by
+    /// `keep_child(child index, variation depth of the parent)` and
+    /// properties accepted by `keep_prop`.
+    fn filtered(
+        &self,
+        keep_child: impl Fn(usize, usize) -> bool,
+        keep_prop: impl Fn(&SGFProperty) -> bool,
+    ) -> GameTree {
+        let mut out = GameTree {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+        let props = |id: NodeId| -> Vec<SGFProperty> {
+            self.node(id)
+                .properties
+                .iter()
+                .filter(|p| keep_prop(p))
+                .cloned()
+                .collect()
+        };
+
+        for &root in &self.roots {
+            let new_root = out.add_root(props(root));
+            // (old node, new node, variation depth)
+            let mut stack = vec![(root, new_root, 0)];
+            while let Some((old, new, depth)) = stack.pop() {
+                for (idx, &child) in self.node(old).children.iter().enumerate() {
+                    if keep_child(idx, depth) {
+                        let new_child = out.add_node(new, props(child));
+                        stack.push((child, new_child, depth + usize::from(idx > 0)));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sgf::{Board, Cell, parse_sgf, write_sgf};
+
+    const SGF: &str =
+        "(;GM[1]AP[Foo]XX[app];B[pd]C[hi]TR[dd](;W[dd](;B[pp])(;B[dp]))(;W[dp];B[pp]))";
+
+    #[test]
+    fn variation_depth_limits() {
+        let tree = parse_sgf(SGF).unwrap();
+        assert_eq!(
+            write_sgf(&tree.mainline_only()),
+            "(;GM[1]AP[Foo]XX[app];B[pd]C[hi]TR[dd];W[dd];B[pp])"
+        );
+
+        let nested = parse_sgf("(;GM[1];B[pd](;W[dd])(;W[dp](;B[pp])(;B[qq])))").unwrap();
+        assert_eq!(
+            write_sgf(&nested.without_variations_deeper_than(1)),
+            "(;GM[1];B[pd](;W[dd])(;W[dp];B[pp]))"
+        );
+    }
+
+    #[test]
+    fn property_filters() {
+        let tree = parse_sgf(SGF).unwrap().mainline_only();
+        assert_eq!(
+            write_sgf(&tree.without_comments()),
+            "(;GM[1]AP[Foo]XX[app];B[pd]TR[dd];W[dd];B[pp])"
+        );
+        assert_eq!(
+            write_sgf(&tree.without_markup()),
+            "(;GM[1]AP[Foo]XX[app];B[pd]C[hi];W[dd];B[pp])"
+        );
+        assert_eq!(
+            write_sgf(&tree.without_nonstandard_properties()),
+            "(;GM[1]AP[Foo];B[pd]C[hi]TR[dd];W[dd];B[pp])"
+        );
+    }
+
+    #[test]
+    fn extract_subtree_reconstructs_position() {
+        let tree = parse_sgf("(;GM[1]PB[Alice]AB[aa];B[pd];W[dd](;B[pp])(;B[dp]))").unwrap();
+        let w = tree.node(tree.node(tree.roots[0]).children[0]).children[0];
+        let extracted = tree.extract_subtree(w);
+        assert_eq!(
+            write_sgf(&extracted),
+            "(;GM[1]PB[Alice]AB[aa][pd]AW[dd]PL[B](;B[pp])(;B[dp]))"
+        );
+        let board = Board::from_tree(&extracted, extracted.roots[0]);
+        assert_eq!(board.cells[3][15], Cell::Black);
+        assert_eq!(board.to_play, Cell::Black);
+    }
+}
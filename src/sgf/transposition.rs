@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::sgf::{Board, Cell, GameTree, NodeId, SGFProperty};
+
+/// Identity of a position for transposition purposes: stones, board size and
+/// side to move.  Capture counts and move numbers are deliberately ignored.
+type PositionKey = ([[Cell; 19]; 19], usize, Cell);
+
+/// Groups of nodes in a [`GameTree`] that reach the same position.
+///
+/// Only nodes that play a move (`B` or `W`) are considered; a comment-only
+/// node trivially shares its parent's position and would add noise.  Nodes
+/// from every game record in the tree take part, so transpositions between
+/// games of a collection are found too.
+///
+/// The table is a snapshot: rebuild it with [`Transpositions::find`] after
+/// the tree changes.
+pub struct Transpositions {
+    /// Each group lists its nodes in DFS pre-order; only groups of two or
+    /// more nodes are kept.
+    groups: Vec<Vec<NodeId>>,
+    group_of: HashMap<NodeId, usize>,
+}
+
+impl Transpositions {
+    /// Replay every node of `tree` once and group the move nodes by position.
+    pub fn find(tree: &GameTree) -> Self {
+        let mut by_position: HashMap<PositionKey, Vec<NodeId>> = HashMap::new();
+        let mut order: Vec<PositionKey> = Vec::new();
+
+        for &root in &tree.roots {
+            // Carry each node's board down to its children instead of
+            // replaying from the root for every node.
+            let mut stack = vec![(root, Board::from_tree(tree, root))];
+            while let Some((id, board)) = stack.pop() {
+                let node = tree.node(id);
+                if is_move_node(&node.properties) {
+                    let key = (board.cells, board.size, board.to_play);
+                    let entry = by_position.entry(key).or_insert_with(|| {
+                        order.push(key);
+                        Vec::new()
+                    });
+                    entry.push(id);
+                }
+                for &child in node.children.iter().rev() {
+                    let mut next = board.clone();
+                    next.apply_node(tree.node(child));
+                    stack.push((child, next));
+                }
+            }
+        }
+
+        let groups: Vec<Vec<NodeId>> = order
+            .into_iter()
+            .filter_map(|key| by_position.remove(&key))
+            .filter(|ids| ids.len() > 1)
+            .collect();
+        let group_of = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(g, ids)| ids.iter().map(move |&id| (id, g)))
+            .collect();
+
+        Self { groups, group_of }
+    }
+
+    /// All nodes that reach the same position as `id`, including `id`
+    /// itself.  Empty if `id` has no transposition.
+    pub fn of(&self, id: NodeId) -> &[NodeId] {
+        match self.group_of.get(&id) {
+            Some(&g) => &self.groups[g],
+            None => &[],
+        }
+    }
+
+    /// Iterate over every group of transposed nodes.
+    pub fn groups(&self) -> impl Iterator<Item = &[NodeId]> {
+        self.groups.iter().map(Vec::as_slice)
+    }
+}
+
+fn is_move_node(props: &[SGFProperty]) -> bool {
+    props
+        .iter()
+        .any(|p| matches!(p, SGFProperty::B(_) | SGFProperty::W(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sgf::parse_sgf;
+
+    #[test]
+    fn different_move_orders_are_grouped() {
+        let tree =
+            parse_sgf("(;GM[1](;B[dd];W[pp];B[pd])(;B[pd];W[pp];B[dd])(;B[pp];W[pd]))").unwrap();
+        let root = tree.roots[0];
+        let leaf = |branch: usize| {
+            let mut id = tree.node(root).children[branch];
+            while let Some(&c) = tree.node(id).children.first() {
+                id = c;
+            }
+            id
+        };
+        let t = Transpositions::find(&tree);
+        assert_eq!(t.groups().count(), 1);
+        assert_eq!(t.of(leaf(0)), &[leaf(0), leaf(1)]);
+        assert!(t.of(leaf(2)).is_empty());
+    }
+
+    #[test]
+    fn side_to_move_distinguishes_positions() {
+        // Both lines end with the same stones, but `PL` hands the move back
+        // to white in the second line.
+        let tree = parse_sgf("(;GM[1](;B[dd];W[pp])(;B[dd];W[pp]PL[W]))").unwrap();
+        let root = tree.roots[0];
+        let second = tree.node(tree.node(root).children[1]).children[0];
+        let t = Transpositions::find(&tree);
+        // Only the two `B[dd]` nodes transpose.
+        assert_eq!(t.groups().count(), 1);
+        assert!(t.of(second).is_empty());
+    }
+}
@@ -1,3 +1,7 @@
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Result, bail, ensure};
+
 use crate::sgf::node::SGFProperty;
 
 /// Index into the [`GameTree`] arena.
@@ -12,7 +16,7 @@ pub type NodeId = usize;
 /// Each node holds a list of [`SGFProperty`] values (the properties written
 /// between `;` markers in the SGF file), a link to its parent, and an ordered
 /// list of children (variations).
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TreeNode {
     pub properties: Vec<SGFProperty>,
     pub parent: Option<NodeId>,
@@ -27,7 +31,7 @@ pub struct TreeNode {
 ///
 /// A file that contains multiple game records results in a tree with multiple
 /// entries in `roots`.
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct GameTree {
     /// Arena storage — private to external users; pub(crate) for the parser.
     pub(crate) nodes: Vec<TreeNode>,
@@ -73,6 +77,19 @@ impl GameTree {
         &mut self.nodes[id]
     }
 
+    /// Append a new top-level game record whose root node holds `props` and
+    /// return its `NodeId`.
+    pub fn add_root(&mut self, props: Vec<SGFProperty>) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(TreeNode {
+            properties: props,
+            parent: None,
+            children: Vec::new(),
+        });
+        self.roots.push(id);
+        id
+    }
+
     /// Append a new child node under `parent` and return its `NodeId`.
     pub fn add_node(&mut self, parent: NodeId, props: Vec<SGFProperty>) -> NodeId {
         let id = self.nodes.len();
@@ -98,6 +115,116 @@ impl GameTree {
         }
     }
 
+    /// Make `id` part of the main line by moving it, and every ancestor of
+    /// it, to the front of its parent's children list.
+    ///
+    /// The relative order of the remaining siblings is preserved.  Calling
+    /// this on a root node is a no-op.
+    pub fn promote_variation(&mut self, id: NodeId) {
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            self.reorder_variation(current, 0);
+            current = parent;
+        }
+    }
+
+    /// Move `id` to position `index` among its siblings.
+    ///
+    /// `index` is clamped to the last position, so `usize::MAX` moves the
+    /// variation to the end.  Calling this on a root node is a no-op.
+    pub fn reorder_variation(&mut self, id: NodeId, index: usize) {
+        let Some(parent) = self.nodes[id].parent else {
+            return;
+        };
+        let siblings = &mut self.nodes[parent].children;
+        if let Some(pos) = siblings.iter().position(|&c| c == id) {
+            siblings.remove(pos);
+            let index = index.min(siblings.len());
+            siblings.insert(index, id);
+        }
+    }
+
+    /// Return `true` if `ancestor` lies on the path from `id` up to its root
+    /// (a node counts as its own ancestor).
+    pub fn is_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+        let mut current = Some(id);
+        while let Some(c) = current {
+            if c == ancestor {
+                return true;
+            }
+            current = self.nodes[c].parent;
+        }
+        false
+    }
+
+    /// Detach the subtree rooted at `id` and re-attach it as the last child of
+    /// `new_parent`.  `new_parent` may belong to a different game record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `id` is a game root, or if `new_parent` lies inside
+    /// the subtree being moved (which would create a cycle).
+    pub fn move_subtree(&mut self, id: NodeId, new_parent: NodeId) -> Result<()> {
+        let Some(old_parent) = self.nodes[id].parent else {
+            bail!("Cannot move node {id}: it is the root of a game record");
+        };
+        ensure!(
+            !self.is_ancestor(id, new_parent),
+            "Cannot move node {id} under its own descendant {new_parent}"
+        );
+        self.nodes[old_parent].children.retain(|&c| c != id);
+        self.nodes[id].parent = Some(new_parent);
+        self.nodes[new_parent].children.push(id);
+        Ok(())
+    }
+
+    /// Copy the subtree rooted at `id` and append the copy as the last child
+    /// of `new_parent`.  Returns the `NodeId` of the copied subtree root.
+    ///
+    /// The copy is taken before anything is inserted, so `new_parent` may lie
+    /// inside the subtree being copied.
+    pub fn copy_subtree(&mut self, id: NodeId, new_parent: NodeId) -> NodeId {
+        let entries = self.collect_subtree(id);
+        self.insert_entries(entries, new_parent)
+    }
+
+    /// Copy the subtree rooted at `id` in another tree (`source`) and append it
+    /// as the last child of `new_parent` in this tree.  Returns the `NodeId` of
+    /// the copied subtree root.
+    pub fn graft_subtree(&mut self, source: &GameTree, id: NodeId, new_parent: NodeId) -> NodeId {
+        let entries = source.collect_subtree(id);
+        self.insert_entries(entries, new_parent)
+    }
+
+    /// Snapshot a subtree in DFS pre-order as `(id, parent, properties)`
+    /// triples.  Parents always appear before their children.
+    fn collect_subtree(&self, id: NodeId) -> Vec<(NodeId, Option<NodeId>, Vec<SGFProperty>)> {
+        self.iter_subtree(id)
+            .map(|(nid, node)| (nid, node.parent, node.properties.clone()))
+            .collect()
+    }
+
+    /// Insert entries produced by [`collect_subtree`](Self::collect_subtree),
+    /// attaching the first entry to `new_parent`.
+    fn insert_entries(
+        &mut self,
+        entries: Vec<(NodeId, Option<NodeId>, Vec<SGFProperty>)>,
+        new_parent: NodeId,
+    ) -> NodeId {
+        let mut mapping: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut first = None;
+        for (old_id, old_parent, props) in entries {
+            let parent = match first {
+                None => new_parent,
+                Some(_) => mapping[&old_parent.expect("non-root subtree node has a parent")],
+            };
+            let new_id = self.add_node(parent, props);
+            mapping.insert(old_id, new_id);
+            first.get_or_insert(new_id);
+        }
+        first.expect("subtree contains at least its root")
+    }
+
     pub fn iter_mainline(&self, start: NodeId) -> MainlineIter<'_> {
         MainlineIter {
             tree: self,
@@ -111,6 +238,71 @@ impl GameTree {
             stack: vec![start],
         }
     }
+
+    /// Iterate from `start` up to its root, `start` included.
+    pub fn iter_ancestors(&self, start: NodeId) -> AncestorsIter<'_> {
+        AncestorsIter {
+            tree: self,
+            current: Some(start),
+        }
+    }
+
+    /// Iterate over the leaves (nodes without children) below `start`, in
+    /// DFS pre-order.
+    pub fn iter_leaves(&self, start: NodeId) -> LeavesIter<'_> {
+        LeavesIter {
+            inner: self.iter_subtree(start),
+        }
+    }
+
+    /// Iterate over every line of play below `start`: one path from `start`
+    /// to each leaf, in DFS order.
+    pub fn iter_variations(&self, start: NodeId) -> VariationsIter<'_> {
+        VariationsIter {
+            leaves: self.iter_leaves(start),
+            start,
+        }
+    }
+
+    /// Breadth-first traversal over every node reachable from `start`.
+    pub fn iter_breadth_first(&self, start: NodeId) -> BreadthFirstIter<'_> {
+        BreadthFirstIter {
+            tree: self,
+            queue: VecDeque::from([start]),
+        }
+    }
+
+    /// DFS post-order traversal: every node is yielded after all of its
+    /// children.
+    pub fn iter_post_order(&self, start: NodeId) -> PostOrderIter<'_> {
+        PostOrderIter {
+            tree: self,
+            stack: vec![(start, 0)],
+        }
+    }
+
+    /// Iterate over the other children of `id`'s parent, in order.  For a
+    /// root node the siblings are the other game records.
+    pub fn iter_siblings(&self, id: NodeId) -> SiblingsIter<'_> {
+        let siblings = match self.nodes[id].parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        };
+        SiblingsIter {
+            tree: self,
+            siblings: siblings.iter(),
+            skip: id,
+        }
+    }
+
+    /// DFS pre-order traversal that also yields each node's depth relative
+    /// to `start` (which has depth 0).
+    pub fn iter_with_depth(&self, start: NodeId) -> DepthIter<'_> {
+        DepthIter {
+            tree: self,
+            stack: vec![(start, 0)],
+        }
+    }
 }
 
 /// Follows the first child of each node (the main line of play).
@@ -147,3 +339,179 @@ impl<'a> Iterator for SubtreeIter<'a> {
         Some((id, node))
     }
 }
+
+/// Follows parent links from a node up to its root.
+pub struct AncestorsIter<'a> {
+    tree: &'a GameTree,
+    current: Option<NodeId>,
+}
+
+impl<'a> Iterator for AncestorsIter<'a> {
+    type Item = (NodeId, &'a TreeNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.current?;
+        let node = &self.tree.nodes[id];
+        self.current = node.parent;
+        Some((id, node))
+    }
+}
+
+/// Leaves of a subtree, in DFS pre-order.
+pub struct LeavesIter<'a> {
+    inner: SubtreeIter<'a>,
+}
+
+impl<'a> Iterator for LeavesIter<'a> {
+    type Item = (NodeId, &'a TreeNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.find(|(_, node)| node.children.is_empty())
+    }
+}
+
+/// Root-to-leaf paths of a subtree.  Each item lists the `NodeId`s from the
+/// starting node down to one leaf.
+pub struct VariationsIter<'a> {
+    leaves: LeavesIter<'a>,
+    start: NodeId,
+}
+
+impl Iterator for VariationsIter<'_> {
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (leaf, _) = self.leaves.next()?;
+        let mut path = Vec::new();
+        for (id, _) in self.leaves.inner.tree.iter_ancestors(leaf) {
+            path.push(id);
+            if id == self.start {
+                break;
+            }
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first traversal over every node reachable from a given root.
+pub struct BreadthFirstIter<'a> {
+    tree: &'a GameTree,
+    queue: VecDeque<NodeId>,
+}
+
+impl<'a> Iterator for BreadthFirstIter<'a> {
+    type Item = (NodeId, &'a TreeNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.queue.pop_front()?;
+        let node = &self.tree.nodes[id];
+        self.queue.extend(node.children.iter().copied());
+        Some((id, node))
+    }
+}
+
+/// DFS post-order traversal over every node reachable from a given root.
+pub struct PostOrderIter<'a> {
+    tree: &'a GameTree,
+    /// `(node, index of the next child to descend into)`.
+    stack: Vec<(NodeId, usize)>,
+}
+
+impl<'a> Iterator for PostOrderIter<'a> {
+    type Item = (NodeId, &'a TreeNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (id, next_child) = self.stack.last_mut()?;
+            let node = &self.tree.nodes[*id];
+            match node.children.get(*next_child) {
+                Some(&child) => {
+                    *next_child += 1;
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    let id = *id;
+                    self.stack.pop();
+                    return Some((id, node));
+                }
+            }
+        }
+    }
+}
+
+/// The other children of a node's parent.
+pub struct SiblingsIter<'a> {
+    tree: &'a GameTree,
+    siblings: std::slice::Iter<'a, NodeId>,
+    skip: NodeId,
+}
+
+impl<'a> Iterator for SiblingsIter<'a> {
+    type Item = (NodeId, &'a TreeNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = *self.siblings.find(|&&id| id != self.skip)?;
+        Some((id, &self.tree.nodes[id]))
+    }
+}
+
+/// DFS pre-order traversal yielding `(depth, id, node)`.
+pub struct DepthIter<'a> {
+    tree: &'a GameTree,
+    stack: Vec<(NodeId, usize)>,
+}
+
+impl<'a> Iterator for DepthIter<'a> {
+    type Item = (usize, NodeId, &'a TreeNode);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.stack.pop()?;
+        let node = &self.tree.nodes[id];
+        self.stack
+            .extend(node.children.iter().rev().map(|&c| (c, depth + 1)));
+        Some((depth, id, node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sgf::{NodeId, parse_sgf};
+
+    /// Node ids in parse order: 0 root, 1 B[aa], 2 W[bb], 3 B[cc], 4 W[dd], 5 W[ee].
+    const SGF: &str = "(;GM[1];B[aa](;W[bb];B[cc])(;W[dd])(;W[ee]))";
+
+    fn ids<T>(it: impl Iterator<Item = (NodeId, T)>) -> Vec<NodeId> {
+        it.map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn ancestors_leaves_and_variations() {
+        let tree = parse_sgf(SGF).unwrap();
+        assert_eq!(ids(tree.iter_ancestors(3)), vec![3, 2, 1, 0]);
+        assert_eq!(ids(tree.iter_leaves(0)), vec![3, 4, 5]);
+        assert_eq!(
+            tree.iter_variations(1).collect::<Vec<_>>(),
+            vec![vec![1, 2, 3], vec![1, 4], vec![1, 5]]
+        );
+    }
+
+    #[test]
+    fn breadth_first_and_post_order() {
+        let tree = parse_sgf(SGF).unwrap();
+        assert_eq!(ids(tree.iter_breadth_first(0)), vec![0, 1, 2, 4, 5, 3]);
+        assert_eq!(ids(tree.iter_post_order(0)), vec![3, 2, 4, 5, 1, 0]);
+    }
+
+    #[test]
+    fn siblings_and_depth() {
+        let tree = parse_sgf(SGF).unwrap();
+        assert_eq!(ids(tree.iter_siblings(4)), vec![2, 5]);
+        assert_eq!(ids(tree.iter_siblings(0)), Vec::<NodeId>::new());
+        let depths: Vec<(usize, NodeId)> = tree
+            .iter_with_depth(1)
+            .map(|(depth, id, _)| (depth, id))
+            .collect();
+        assert_eq!(depths, vec![(0, 1), (1, 2), (2, 3), (1, 4), (1, 5)]);
+    }
+}
@@ -0,0 +1,49 @@
+//! Zobrist keys for incremental position hashing.
+//!
+//! The table is generated at compile time from a fixed seed, so hashes are
+//! stable across runs and builds and can be stored or compared between
+//! processes.
+
+use crate::sgf::Cell;
+
+const SEED: u64 = 0x7E5C_1A1B_0A2D_0C19;
+
+/// One 64-bit step of the SplitMix64 generator.
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+/// `STONES[row][col][0]` for a black stone, `[1]` for a white stone.
+const STONES: [[[u64; 2]; 19]; 19] = {
+    let mut table = [[[0; 2]; 19]; 19];
+    let mut state = SEED;
+    let mut row = 0;
+    while row < 19 {
+        let mut col = 0;
+        while col < 19 {
+            let (s, black) = splitmix64(state);
+            let (s, white) = splitmix64(s);
+            table[row][col] = [black, white];
+            state = s;
+            col += 1;
+        }
+        row += 1;
+    }
+    table
+};
+
+/// Mixed into the hash when white is to play (situational superko).
+pub(crate) const WHITE_TO_PLAY: u64 = splitmix64(SEED ^ 0xFFFF_FFFF).1;
+
+/// Key for a stone of `color` at `(row, col)`; 0 for [`Cell::Empty`].
+pub(crate) fn stone_key(row: usize, col: usize, color: Cell) -> u64 {
+    match color {
+        Cell::Empty => 0,
+        Cell::Black => STONES[row][col][0],
+        Cell::White => STONES[row][col][1],
+    }
+}